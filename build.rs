@@ -0,0 +1,4 @@
+// The migrations are built into the library, so adding one has to rebuild it
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,3 @@
+-- Whether each player had played their initial meld by that turn, kept alongside their rack
+
+ALTER TABLE rustikub.player_rack_turn ADD COLUMN melded boolean NOT NULL DEFAULT false;
@@ -0,0 +1,38 @@
+use rustikub::persistence::postgres::{PostgresRepository, DATABASE_URL};
+use std::env;
+
+/// Shows every migration of the rustikub schema in DATABASE_URL, after applying any missing ones
+/// for db migrate
+pub fn db(command: Option<&str>) {
+    let migrate = match command {
+        Some("migrate") => true,
+        Some("status") => false,
+        _ => {
+            println!("Usage: rustikub db <migrate|status>");
+            return;
+        }
+    };
+    let Ok(url) = env::var(DATABASE_URL) else {
+        eprintln!("Set {} to the database to use", DATABASE_URL);
+        std::process::exit(1);
+    };
+    let runtime = tokio::runtime::Runtime::new().expect("Could not start the runtime");
+    let result = runtime.block_on(async {
+        let repository = PostgresRepository::connect_as_is(&url).await?;
+        if migrate {
+            repository.migrate().await?;
+        }
+        repository.migration_status().await
+    });
+    match result {
+        Ok(status) => {
+            for migration in status {
+                println!("{}", migration);
+            }
+        }
+        Err(e) => {
+            eprintln!("Database error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,9 @@
+//! The command line modes of the rustikub binary, separate from the library which does the playing
+
+#[cfg(feature = "postgres")]
+pub mod db;
+pub mod narrator;
+pub mod play;
+pub mod replay;
+pub mod simulate;
+pub mod tui;
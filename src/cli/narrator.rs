@@ -0,0 +1,26 @@
+use log::{LevelFilter, Log, Metadata, Record};
+
+/// Prints what the game has to say about every turn, as it is said. Only the library's own
+/// messages are printed, not those of the crates it uses
+struct Narrator;
+
+impl Log for Narrator {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level() && metadata.target().starts_with("rustikub")
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            println!("{}", record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Narrates the game in modes which want it, everything up to the given level. Without it,
+/// nothing the game says is printed at all
+pub fn narrate(level: LevelFilter) {
+    log::set_logger(&Narrator).expect("Only narrated once");
+    log::set_max_level(level);
+}
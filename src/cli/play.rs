@@ -0,0 +1,221 @@
+use crate::cli::replay::recorder;
+use rustikub::domain::player::rack::Rack;
+use rustikub::domain::sets::run::Slot;
+use rustikub::domain::table::face_up::FaceUpTiles;
+use rustikub::game_loop::human::{HandMove, TurnInProgress};
+use rustikub::game_loop::observation::Observation;
+use rustikub::game_loop::strategy::{all_strategies, MovePath, Strategy};
+use rustikub::{main_game_loop_with_events, GameConfig, GameState};
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::sync::{Arc, Mutex};
+
+const HELP: &str = "Commands:
+  place <set>                   lay down a new set from your rack, e.g. place R5 B5 K5 or place O3-O7
+  add <tile> <#set> [slot]      add a tile to a set, slot is left, right or a position to wedge in
+  split <#set> <tiles>          split a run in two, keeping this many tiles in the first
+  take <tile> <#set>            take a spare off a set, it has to be played again this turn
+  undo                          take back the last move
+  done                          end your turn
+  draw                          put everything back and draw a tile instead";
+
+/// Seats one person at the keyboard against the given number of bots
+pub fn play(bots: u8, seed: Option<u64>) {
+    let human: Arc<dyn Strategy> = Arc::new(AtTheKeyboard::new(
+        BufReader::new(io::stdin()),
+        io::stdout(),
+    ));
+    let mut strategies = vec![human];
+    strategies.extend(all_strategies().into_iter().cycle().take(bots as usize));
+    let game = GameState::init_game(GameConfig {
+        num_players: bots + 1,
+        seed,
+        strategies,
+        ..GameConfig::default()
+    });
+    let seed = game.seed;
+    println!("You are player 1, playing against {} bots\n{}", bots, HELP);
+    let result = main_game_loop_with_events(game, &mut recorder());
+    println!("Game Complete! Result: {}", result);
+    // Only once it is over, the seed decides the whole boneyard
+    println!("Game Seed: {}", seed);
+}
+
+/// A strategy which asks the person at the keyboard for every move
+pub struct AtTheKeyboard<R, W> {
+    input: Mutex<R>,
+    output: Mutex<W>,
+}
+
+impl<R: BufRead + Send, W: Write + Send> AtTheKeyboard<R, W> {
+    pub fn new(input: R, output: W) -> Self {
+        AtTheKeyboard {
+            input: Mutex::new(input),
+            output: Mutex::new(output),
+        }
+    }
+}
+
+impl<R: BufRead + Send, W: Write + Send> Strategy for AtTheKeyboard<R, W> {
+    fn name(&self) -> &str {
+        "human"
+    }
+
+    /// Only ever plays whole turns, see propose_turn
+    fn rearrange(&self, _rack: &Rack, _face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+        None
+    }
+
+    fn propose_turn(
+        &self,
+        observation: &Observation,
+    ) -> Option<(Rack, FaceUpTiles, Vec<MovePath>)> {
+        let mut input = self.input.lock().expect("Only used from the game loop");
+        let mut output = self.output.lock().expect("Only used from the game loop");
+        let mut turn = TurnInProgress::new(observation);
+        writeln!(output, "\n=== Your Turn ===").unwrap();
+        writeln!(output, "Boneyard: {} tiles", observation.boneyard).unwrap();
+        for opponent in &observation.opponents {
+            writeln!(output, "{}: {} tiles", opponent.info, opponent.tiles).unwrap();
+        }
+        show(&turn, &mut *output);
+
+        let mut line = String::new();
+        loop {
+            write!(output, "> ").unwrap();
+            output.flush().unwrap();
+            line.clear();
+            // Running out of input is taken as drawing, so a game can never hang waiting
+            if input.read_line(&mut line).unwrap_or(0) == 0 {
+                return None;
+            }
+            let mut words = line.split_whitespace();
+            let made = match words.next() {
+                Some("done") | Some("end") => match turn.finish() {
+                    Ok((rack, face_up)) => return Some((rack, face_up, vec![MovePath::ByHand])),
+                    Err(violation) => Err(format!("Can't end the turn like this: {}", violation)),
+                },
+                Some("draw") => return None,
+                Some("undo") => match turn.undo() {
+                    true => Ok(()),
+                    false => Err("Nothing left to undo".to_string()),
+                },
+                Some(command) => read_move(command, words.collect())
+                    .and_then(|hand_move| turn.make(hand_move).map_err(|e| e.to_string())),
+                None => Err(HELP.to_string()),
+            };
+            match made {
+                Ok(()) => show(&turn, &mut *output),
+                Err(why) => writeln!(output, "{}", why).unwrap(),
+            }
+        }
+    }
+}
+
+/// The table with each set numbered, so they can be referred to, and the rack underneath
+fn show(turn: &TurnInProgress, output: &mut impl Write) {
+    writeln!(output, "Table:").unwrap();
+    for (i, set) in turn.face_up().sets.iter().enumerate() {
+        writeln!(output, "  #{:<3}{}", i, set).unwrap();
+    }
+    writeln!(output, "Your Rack: {}", turn.rack()).unwrap();
+}
+
+fn read_move(command: &str, args: Vec<&str>) -> Result<HandMove, String> {
+    let set_number = |arg: Option<&&str>| -> Result<usize, String> {
+        arg.and_then(|s| s.trim_start_matches('#').parse().ok())
+            .ok_or_else(|| format!("Expected a set number, see '{}'", command))
+    };
+    let tile = |arg: Option<&&str>| {
+        arg.ok_or_else(|| "Expected a tile, like R5 or J".to_string())?
+            .parse()
+            .map_err(|e: rustikub::domain::notation::NotationError| e.to_string())
+    };
+    match command {
+        "place" => args
+            .join(" ")
+            .parse()
+            .map(HandMove::Place)
+            .map_err(|e| e.to_string()),
+        "add" => {
+            let slot = match args.get(2) {
+                None => None,
+                Some(&"left") => Some(Slot::Left),
+                Some(&"right") => Some(Slot::Right),
+                Some(position) => Some(Slot::Wedge(position.parse().map_err(|_| {
+                    "Slot is left, right or the position to wedge the tile in".to_string()
+                })?)),
+            };
+            Ok(HandMove::Add {
+                tile: tile(args.first())?,
+                set: set_number(args.get(1))?,
+                slot,
+            })
+        }
+        "split" => Ok(HandMove::Split {
+            set: set_number(args.first())?,
+            at: args
+                .get(1)
+                .and_then(|at| at.parse().ok())
+                .ok_or("Expected how many tiles to keep in the first run")?,
+        }),
+        "take" => Ok(HandMove::Take {
+            tile: tile(args.first())?,
+            set: set_number(args.get(1))?,
+        }),
+        _ => Err(HELP.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod play_cli_tests {
+    use super::*;
+
+    fn propose(rack: &str, script: &str) -> (Option<String>, String) {
+        let mut output = vec![];
+        let proposed = {
+            let human = AtTheKeyboard::new(script.as_bytes(), &mut output);
+            let seen = Observation::new(&rack.parse().unwrap(), &FaceUpTiles::new());
+            human.propose_turn(&seen).map(|(rack, face_up, via)| {
+                assert_eq!(via, vec![MovePath::ByHand]);
+                format!("{:#} {:#}", rack, face_up)
+            })
+        };
+        (proposed, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn rejects_illegal_turns_until_fixed() {
+        let script = "place R1-R3\ndone\nplace R10-R12\nundo\nundo\nfrob\nplace R10-R12\ndone\n";
+        let (proposed, output) = propose("[R10 R11 R12 K1 R1 R2]", script);
+        assert_eq!(proposed.as_deref(), Some("[R1 R2 K1] R10-R12"));
+        assert!(output.contains("is not on your rack"));
+        assert!(output.contains("Can't end the turn like this: No tiles were placed"));
+        assert!(output.contains("Nothing left to undo"));
+        assert!(output.contains("Commands:"));
+    }
+
+    #[test]
+    fn draws_when_asked_or_out_of_input() {
+        assert_eq!(propose("[R10 R11 R12]", "place R10-R12\ndraw\n").0, None);
+        assert_eq!(propose("[R10 R11 R12]", "").0, None);
+    }
+
+    #[test]
+    fn reads_moves() {
+        assert_eq!(
+            read_move("add", vec!["J", "#2", "left"]),
+            Ok(HandMove::Add {
+                tile: "J".parse().unwrap(),
+                set: 2,
+                slot: Some(Slot::Left)
+            })
+        );
+        assert_eq!(
+            read_move("split", vec!["0", "3"]),
+            Ok(HandMove::Split { set: 0, at: 3 })
+        );
+        assert!(read_move("take", vec!["R5"]).is_err());
+        assert!(read_move("place", vec!["R5", "B6"]).is_err());
+    }
+}
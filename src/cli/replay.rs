@@ -0,0 +1,135 @@
+use rustikub::game_loop::debugger::ReplayDebugger;
+use rustikub::game_loop::events::{read_events, JsonLines};
+use rustikub::{main_game_loop_with_events, GameConfig, GameEvent, GameState};
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+
+const HELP: &str = "Commands: [n]ext (or just enter), [p]revious, [g]oto <turn>, [b]roken, [q]uit";
+
+/// Where play, tui and the default game record their events as they happen
+pub const LAST_GAME: &str = "last_game.jsonl";
+
+/// Records every event to LAST_GAME, or nowhere if the file can't be created
+pub fn recorder() -> JsonLines<Box<dyn Write>> {
+    match File::create(LAST_GAME) {
+        Ok(file) => JsonLines(Box::new(file)),
+        Err(e) => {
+            println!("Not recording the game to {}: {}", LAST_GAME, e);
+            JsonLines(Box::new(io::sink()))
+        }
+    }
+}
+
+/// Steps through a game recorded to the given file, exactly as it was played
+pub fn replay_file(path: &str) {
+    let events = match File::open(path).map(BufReader::new) {
+        Ok(file) => read_events(file),
+        Err(e) => Err(e.into()),
+    };
+    let events = match events {
+        Ok(events) => events,
+        Err(e) => {
+            println!("Could not load {}: {}", path, e);
+            return;
+        }
+    };
+    let Some(debugger) = ReplayDebugger::from_log(&events) else {
+        println!(
+            "{} does not replay, the events do not fit the game they started",
+            path
+        );
+        return;
+    };
+    step_through(debugger, io::stdin().lock(), io::stdout());
+}
+
+/// Without a recording, plays the game for the given seed again with the default strategies,
+/// recording every event, then steps through it. Only the same as the original game if it was
+/// played by the default strategies too
+pub fn replay_seed(seed: u64, num_players: u8) {
+    let start = GameState::init_game(GameConfig {
+        num_players,
+        seed: Some(seed),
+        ..GameConfig::default()
+    });
+    println!("Recording Game With Seed {}...", seed);
+    let mut events: Vec<GameEvent> = vec![];
+    main_game_loop_with_events(start.clone(), &mut events);
+    let debugger = ReplayDebugger::new(start, &events).expect("Game replays from its own events");
+    step_through(debugger, io::stdin().lock(), io::stdout());
+}
+
+/// Reads commands one line at a time, printing the game after every one of them
+pub fn step_through(mut debugger: ReplayDebugger, input: impl BufRead, mut output: impl Write) {
+    writeln!(output, "\n{}\n{}", debugger, HELP).unwrap();
+    for line in input.lines() {
+        let line = line.unwrap_or_default();
+        let mut words = line.split_whitespace();
+        let moved = match words.next().unwrap_or("n") {
+            "n" | "next" => debugger.forward(),
+            "p" | "prev" | "previous" => debugger.back(),
+            "g" | "goto" => match words.next().and_then(|turn| turn.parse().ok()) {
+                Some(turn) => debugger.goto(turn),
+                None => false,
+            },
+            "b" | "broken" => match debugger.goto_first_broken() {
+                Some(broken) => {
+                    writeln!(
+                        output,
+                        "First broke on turn {}: {}",
+                        debugger.turn(),
+                        broken
+                    )
+                    .unwrap();
+                    true
+                }
+                None => {
+                    writeln!(output, "Every invariant held for the whole game").unwrap();
+                    continue;
+                }
+            },
+            "q" | "quit" => break,
+            _ => {
+                writeln!(output, "{}", HELP).unwrap();
+                continue;
+            }
+        };
+        if moved {
+            writeln!(output, "\n{}", debugger).unwrap();
+        } else {
+            writeln!(output, "Can't go there, at turn {}", debugger.turn()).unwrap();
+        }
+    }
+}
+
+#[cfg(test)]
+mod replay_cli_tests {
+    use super::*;
+
+    #[test]
+    fn steps_from_commands() {
+        let start = GameState::init_game(GameConfig {
+            num_players: 2,
+            seed: Some(5),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        main_game_loop_with_events(start.clone(), &mut events);
+        let debugger = ReplayDebugger::new(start, &events).unwrap();
+
+        let mut output = vec![];
+        step_through(
+            debugger,
+            "n\n\ng 1\np\np\nb\nq\nn\n".as_bytes(),
+            &mut output,
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("--- Turn 2 of"));
+        assert_eq!(output.matches("Can't go there").count(), 1);
+        assert!(output.contains("Can't go there, at turn 0"));
+        assert!(output.contains("Every invariant held for the whole game"));
+        // Nothing after quitting
+        assert_eq!(output.matches("--- Turn").count(), 5);
+    }
+}
@@ -0,0 +1,38 @@
+use rustikub::game_loop::strategy::{all_strategies, strategy_by_name, Strategy};
+use rustikub::{simulate, SimulationConfig};
+use std::sync::Arc;
+
+/// Plays the games without narrating any of them, then prints the report.
+/// Every named strategy plays, or all of the built-in ones if there are none
+pub fn run_simulation(games: u32, player_counts: Vec<u8>, names: &[&str], seed: Option<u64>) {
+    if player_counts.is_empty() || player_counts.iter().any(|n| !(2..=4).contains(n)) {
+        println!("Games are for 2 to 4 players");
+        return;
+    }
+    let strategies: Vec<Arc<dyn Strategy>> = match names {
+        [] | ["all"] => all_strategies(),
+        names => {
+            let found: Option<Vec<Arc<dyn Strategy>>> =
+                names.iter().map(|name| strategy_by_name(name)).collect();
+            let Some(found) = found else {
+                let known: Vec<String> = all_strategies()
+                    .iter()
+                    .map(|s| s.name().to_string())
+                    .collect();
+                println!("Strategies are all, or any of: {}", known.join(", "));
+                return;
+            };
+            found
+        }
+    };
+
+    let conf = SimulationConfig {
+        games,
+        player_counts,
+        strategies,
+        seed,
+        ..SimulationConfig::default()
+    };
+    println!("Simulating {} games, {} at a time...", games, conf.threads);
+    println!("{}", simulate(conf));
+}
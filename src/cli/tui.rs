@@ -0,0 +1,620 @@
+use crate::cli::replay::recorder;
+use ratatui::backend::CrosstermBackend;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{Frame, Terminal};
+use rustikub::domain::player::info::PlayerInfo;
+use rustikub::domain::player::rack::Rack;
+use rustikub::domain::sets::group::Group;
+use rustikub::domain::sets::run::Slot::{Left, Right, Wedge};
+use rustikub::domain::sets::run::{Run, Slot};
+use rustikub::domain::sets::Set;
+use rustikub::domain::table::face_up::FaceUpTiles;
+use rustikub::domain::tiles::Tile::{JokersWild, RegularTile};
+use rustikub::domain::tiles::{color, Tile};
+use rustikub::domain::Decompose;
+use rustikub::game_loop::human::{HandMove, TurnInProgress};
+use rustikub::game_loop::observation::{Observation, Seen};
+use rustikub::game_loop::strategy::{all_strategies, MovePath, Strategy};
+use rustikub::{main_game_loop_with_events, EventSink, GameConfig, GameEvent, GameState};
+use std::io;
+use std::sync::{Arc, Mutex};
+
+const KEYS: &str =
+    "←→ rack  enter pick up/drop  space mark  p place marked  s sort  u undo  d done  x draw";
+
+/// Seats one person at a full screen terminal against the given number of bots
+pub fn play_tui(bots: u8, seed: Option<u64>) {
+    let log = Arc::new(Mutex::new(vec![]));
+    let human: Arc<dyn Strategy> = Arc::new(TuiPlayer { log: log.clone() });
+    let mut strategies = vec![human];
+    strategies.extend(all_strategies().into_iter().cycle().take(bots as usize));
+    let game = GameState::init_game(GameConfig {
+        num_players: bots + 1,
+        seed,
+        strategies,
+        ..GameConfig::default()
+    });
+    let seed = game.seed;
+    let shared = SharedLog {
+        seat: game.players[0].info.clone(),
+        log,
+    };
+    let result = main_game_loop_with_events(game, &mut (shared, recorder()));
+    println!("Game Complete! Result: {}", result);
+    // Only once it is over, the seed decides the whole boneyard
+    println!("Game Seed: {}", seed);
+}
+
+/// Keeps every event of the game as the person at the screen saw it, for the log pane
+struct SharedLog {
+    seat: PlayerInfo,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl EventSink for SharedLog {
+    fn record(&mut self, event: &GameEvent) {
+        let seen = Seen::by(&self.seat, event);
+        self.log.lock().unwrap().push(format!("{:#}", seen));
+    }
+}
+
+/// Takes over the whole terminal for each of the person's turns, and hands it back to the
+/// game's usual output for the bots' turns
+pub struct TuiPlayer {
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Strategy for TuiPlayer {
+    fn name(&self) -> &str {
+        "human"
+    }
+
+    /// Only ever plays whole turns, see propose_turn
+    fn rearrange(&self, _rack: &Rack, _face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+        None
+    }
+
+    fn propose_turn(
+        &self,
+        observation: &Observation,
+    ) -> Option<(Rack, FaceUpTiles, Vec<MovePath>)> {
+        let log = self.log.lock().unwrap().clone();
+        let mut screen = TuiTurn::new(observation, log);
+        let decision = run_screen(&mut screen).unwrap_or(Decision::Draw);
+        match decision {
+            Decision::Play(rack, face_up) => Some((rack, face_up, vec![MovePath::ByHand])),
+            Decision::Draw => None,
+        }
+    }
+}
+
+/// Puts the terminal back the way it was, even if drawing fails part way through
+struct FullScreen;
+
+impl FullScreen {
+    fn enter() -> io::Result<FullScreen> {
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        Ok(FullScreen)
+    }
+}
+
+impl Drop for FullScreen {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+fn run_screen(screen: &mut TuiTurn) -> io::Result<Decision> {
+    let full_screen = FullScreen::enter()?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    loop {
+        terminal.draw(|frame| screen.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        // Raw mode swallows the usual interrupt
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            drop(full_screen);
+            std::process::exit(130);
+        }
+        if let Some(decision) = screen.handle(key.code) {
+            return Ok(decision);
+        }
+    }
+}
+
+/// How the person's turn ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decision {
+    /// A turn which has already passed validation
+    Play(Rack, FaceUpTiles),
+    Draw,
+}
+
+/// Somewhere on the table a tile could be dropped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DropTarget {
+    pub set: usize,
+    /// Only for runs, groups have no order
+    pub slot: Option<Slot>,
+}
+
+/// Every place on the table the tile fits, in table order. Runs offer the slots from
+/// Run::all_possible_slots, or either end for a joker, and groups take whatever they can insert
+pub fn drop_targets(face_up: &FaceUpTiles, tile: Tile) -> Vec<DropTarget> {
+    let mut targets = vec![];
+    for (i, set) in face_up.sets.iter().enumerate() {
+        match (set, tile) {
+            (Set::Group(group), _) => {
+                if group.insert_tile(&tile).is_some() {
+                    targets.push(DropTarget { set: i, slot: None });
+                }
+            }
+            (Set::Run(run), JokersWild) => {
+                for slot in [Left, Right] {
+                    if run.insert_tile(tile, slot).is_some() {
+                        targets.push(DropTarget {
+                            set: i,
+                            slot: Some(slot),
+                        });
+                    }
+                }
+            }
+            (Set::Run(run), RegularTile(..)) => {
+                if let Some(slot) = run.all_possible_slots().and_then(|s| s.get(&tile).copied()) {
+                    targets.push(DropTarget {
+                        set: i,
+                        slot: Some(slot),
+                    });
+                }
+            }
+        }
+    }
+    targets
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortBy {
+    Color,
+    Number,
+}
+
+/// A tile picked up off the rack, along with everywhere it can go
+struct Holding {
+    tile: Tile,
+    targets: Vec<DropTarget>,
+    at: usize,
+}
+
+/// Everything on screen during one of the person's turns, separate from the terminal so it
+/// can be driven by keys in tests
+pub struct TuiTurn {
+    turn: TurnInProgress,
+    boneyard: usize,
+    log: Vec<String>,
+    sort: SortBy,
+    /// Position in the rack, as sorted on screen
+    cursor: usize,
+    marked: Vec<usize>,
+    holding: Option<Holding>,
+    scroll: u16,
+    message: String,
+}
+
+impl TuiTurn {
+    pub fn new(observation: &Observation, log: Vec<String>) -> TuiTurn {
+        TuiTurn {
+            turn: TurnInProgress::new(observation),
+            boneyard: observation.boneyard,
+            log,
+            sort: SortBy::Color,
+            cursor: 0,
+            marked: vec![],
+            holding: None,
+            scroll: 0,
+            message: String::new(),
+        }
+    }
+
+    /// The rack in the order it is shown
+    fn rack_view(&self) -> Vec<Tile> {
+        let mut tiles = self.turn.rack().decompose();
+        // Jokers always go last
+        match self.sort {
+            SortBy::Color => tiles.sort_by_key(|t| (t.is_joker(), *t)),
+            SortBy::Number => tiles.sort_by_key(|t| match t {
+                RegularTile(color, num) => (false, Some(*num), Some(*color)),
+                JokersWild => (true, None, None),
+            }),
+        }
+        tiles
+    }
+
+    /// Returns how the turn ended, once it has
+    pub fn handle(&mut self, key: KeyCode) -> Option<Decision> {
+        self.message.clear();
+        if let Some(holding) = &mut self.holding {
+            let count = holding.targets.len();
+            match key {
+                KeyCode::Left | KeyCode::Up => holding.at = (holding.at + count - 1) % count,
+                KeyCode::Right | KeyCode::Down => holding.at = (holding.at + 1) % count,
+                KeyCode::Enter => {
+                    let target = holding.targets[holding.at];
+                    let tile = holding.tile;
+                    self.holding = None;
+                    self.make(HandMove::Add {
+                        tile,
+                        set: target.set,
+                        slot: target.slot,
+                    });
+                    return None;
+                }
+                KeyCode::Esc => {
+                    self.holding = None;
+                    return None;
+                }
+                _ => {}
+            }
+            let set = holding.targets[holding.at].set as u16;
+            self.scroll = set.saturating_sub(2);
+            return None;
+        }
+
+        let rack = self.rack_view();
+        match key {
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(rack.len().saturating_sub(1)),
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => {
+                self.scroll = (self.scroll + 1).min(self.turn.face_up().sets.len() as u16)
+            }
+            KeyCode::Char(' ') if self.cursor < rack.len() => {
+                match self.marked.iter().position(|&i| i == self.cursor) {
+                    Some(pos) => _ = self.marked.remove(pos),
+                    None => self.marked.push(self.cursor),
+                }
+            }
+            KeyCode::Esc => self.marked.clear(),
+            KeyCode::Enter if self.cursor < rack.len() => {
+                let tile = rack[self.cursor];
+                let targets = drop_targets(self.turn.face_up(), tile);
+                match targets.first() {
+                    Some(first) => {
+                        self.scroll = (first.set as u16).saturating_sub(2);
+                        self.holding = Some(Holding {
+                            tile,
+                            targets,
+                            at: 0,
+                        })
+                    }
+                    None => self.message = format!("{:#} does not fit anywhere on the table", tile),
+                }
+            }
+            KeyCode::Char('p') => {
+                let tiles: Vec<Tile> = self.marked.iter().map(|&i| rack[i]).collect();
+                match marked_set(tiles) {
+                    Some(set) => self.make(HandMove::Place(set)),
+                    None => self.message = "Mark the tiles of a run or group first".to_string(),
+                }
+            }
+            KeyCode::Char('s') => {
+                self.sort = match self.sort {
+                    SortBy::Color => SortBy::Number,
+                    SortBy::Number => SortBy::Color,
+                };
+                self.marked.clear();
+            }
+            KeyCode::Char('u') => {
+                if self.turn.undo() {
+                    self.rack_changed();
+                } else {
+                    self.message = "Nothing left to undo".to_string();
+                }
+            }
+            KeyCode::Char('d') => match self.turn.finish() {
+                Ok((rack, face_up)) => return Some(Decision::Play(rack, face_up)),
+                Err(violation) => {
+                    self.message = format!("Can't end the turn like this: {:#}", violation)
+                }
+            },
+            KeyCode::Char('x') => return Some(Decision::Draw),
+            _ => {}
+        }
+        None
+    }
+
+    fn make(&mut self, hand_move: HandMove) {
+        match self.turn.make(hand_move) {
+            Ok(()) => self.rack_changed(),
+            Err(e) => self.message = format!("{:#}", e),
+        }
+    }
+
+    fn rack_changed(&mut self) {
+        self.marked.clear();
+        self.cursor = self.cursor.min(self.rack_view().len().saturating_sub(1));
+    }
+
+    pub fn draw(&self, frame: &mut Frame) {
+        let [top, rack_area, help_area] = Layout::vertical([
+            Constraint::Min(5),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [table_area, log_area] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)]).areas(top);
+
+        let target = self.holding.as_ref().map(|h| h.targets[h.at]);
+        let table: Vec<Line> = self
+            .turn
+            .face_up()
+            .sets
+            .iter()
+            .enumerate()
+            .map(|(i, set)| {
+                let mut tiles = set.decompose();
+                if let Set::Group(_) = set {
+                    tiles.sort_by_key(|t| (t.is_joker(), *t));
+                }
+                let mut spans = vec![Span::raw(format!("#{:<3}", i))];
+                spans.extend(tile_spans(&tiles, |_| Style::default()));
+                if let Some(target) = target.filter(|t| t.set == i) {
+                    let here = match target.slot {
+                        Some(Left) => "◀ on the left".to_string(),
+                        Some(Right) | None => "◀ here".to_string(),
+                        Some(Wedge(pos)) => format!("◀ wedged in at {}", pos),
+                    };
+                    spans.push(Span::styled(
+                        here,
+                        Style::default().add_modifier(Modifier::BOLD),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        let title = format!(" Table ({} sets) ", table.len());
+        frame.render_widget(
+            Paragraph::new(table)
+                .scroll((self.scroll, 0))
+                .block(Block::bordered().title(title)),
+            table_area,
+        );
+
+        let shown = log_area.height.saturating_sub(2) as usize;
+        let log: Vec<Line> = self
+            .log
+            .iter()
+            .skip(self.log.len().saturating_sub(shown))
+            .map(|entry| Line::raw(entry.as_str()))
+            .collect();
+        let title = format!(" Boneyard: {} tiles ", self.boneyard);
+        frame.render_widget(
+            Paragraph::new(log).block(Block::bordered().title(title)),
+            log_area,
+        );
+
+        let rack = self.rack_view();
+        let title = match &self.holding {
+            Some(holding) => format!(" Holding {:#}, ←→ to choose a spot ", holding.tile),
+            None => {
+                let by = match self.sort {
+                    SortBy::Color => "color",
+                    SortBy::Number => "number",
+                };
+                format!(" Your Rack, by {} ({} tiles) ", by, rack.len())
+            }
+        };
+        let spans = tile_spans(&rack, |i| {
+            let mut style = Style::default();
+            if self.marked.contains(&i) {
+                style = style.add_modifier(Modifier::UNDERLINED | Modifier::BOLD);
+            }
+            if i == self.cursor {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            style
+        });
+        frame.render_widget(
+            Paragraph::new(Line::from(spans)).block(Block::bordered().title(title)),
+            rack_area,
+        );
+
+        let help = match self.message.is_empty() {
+            true => KEYS,
+            false => self.message.as_str(),
+        };
+        frame.render_widget(Paragraph::new(help), help_area);
+    }
+}
+
+/// Tiles in the plain notation, each in its own color as on the real tiles
+/// The run or group the marked tiles make, whatever order they were marked in. The numbers of a
+/// run are sorted, then each joker is tried in every gap and at either end, the top end first
+fn marked_set(tiles: Vec<Tile>) -> Option<Set> {
+    let (jokers, mut regular): (Vec<Tile>, Vec<Tile>) =
+        tiles.iter().partition(|t| matches!(t, JokersWild));
+    regular.sort();
+    let mut arrangements = vec![regular];
+    for joker in jokers {
+        arrangements = arrangements
+            .into_iter()
+            .flat_map(|tiles| {
+                (0..=tiles.len()).rev().map(move |at| {
+                    let mut with_joker = tiles.clone();
+                    with_joker.insert(at, joker);
+                    with_joker
+                })
+            })
+            .collect();
+    }
+    arrangements
+        .iter()
+        .find_map(|tiles| Run::parse(tiles))
+        .map(Set::Run)
+        .or_else(|| Group::parse(tiles).map(Set::Group))
+}
+
+fn tile_spans(tiles: &[Tile], style_of: impl Fn(usize) -> Style) -> Vec<Span<'static>> {
+    let mut spans = vec![];
+    for (i, tile) in tiles.iter().enumerate() {
+        let ink = match tile {
+            JokersWild => Color::LightGreen,
+            RegularTile(color::Color::Red, _) => Color::Red,
+            RegularTile(color::Color::Blue, _) => Color::Blue,
+            RegularTile(color::Color::Orange, _) => Color::Yellow,
+            RegularTile(color::Color::Black, _) => Color::White,
+        };
+        spans.push(Span::styled(format!("{:#}", tile), style_of(i).fg(ink)));
+        spans.push(Span::raw(" "));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tui_tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn screen(rack: &str, table: &str, melded: bool) -> TuiTurn {
+        let mut rack: Rack = rack.parse().unwrap();
+        rack.played_initial_meld = melded;
+        let mut seen = Observation::new(&rack, &table.parse().unwrap());
+        seen.boneyard = 106;
+        TuiTurn::new(&seen, vec!["1 drew R5".to_string()])
+    }
+
+    fn press(screen: &mut TuiTurn, keys: &str) -> Option<Decision> {
+        let mut decision = None;
+        for key in keys.chars() {
+            let code = match key {
+                '<' => KeyCode::Left,
+                '>' => KeyCode::Right,
+                '\n' => KeyCode::Enter,
+                '!' => KeyCode::Esc,
+                key => KeyCode::Char(key),
+            };
+            decision = screen.handle(code);
+        }
+        decision
+    }
+
+    fn rendered(screen: &TuiTurn) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 16)).unwrap();
+        terminal.draw(|frame| screen.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content().iter().map(|cell| cell.symbol()).collect()
+    }
+
+    #[test]
+    fn drop_targets_come_from_the_sets() {
+        let table: FaceUpTiles = "B4-B6 | R8 O8 K8 | B8-B10".parse().unwrap();
+        let at = |set, slot| DropTarget { set, slot };
+        assert_eq!(
+            drop_targets(&table, "B3".parse().unwrap()),
+            vec![at(0, Some(Left))]
+        );
+        assert_eq!(
+            drop_targets(&table, "B7".parse().unwrap()),
+            vec![at(0, Some(Right)), at(2, Some(Left))]
+        );
+        assert_eq!(
+            drop_targets(&table, "J".parse().unwrap()),
+            vec![
+                at(0, Some(Left)),
+                at(0, Some(Right)),
+                at(1, None),
+                at(2, Some(Left)),
+                at(2, Some(Right))
+            ]
+        );
+        assert_eq!(drop_targets(&table, "K1".parse().unwrap()), vec![]);
+    }
+
+    #[test]
+    fn drag_tiles_onto_the_table() {
+        let mut screen = screen("[B3 B7 B8]", "B4-B6 | R8 O8 K8", true);
+        // B3 onto the only place it fits
+        assert_eq!(press(&mut screen, "\n\n"), None);
+        // B8 onto the group, then B7 onto the end of the run
+        press(&mut screen, ">\n\n");
+        press(&mut screen, "\n>\n");
+        assert_eq!(format!("{:#}", screen.turn.face_up()), "B3-B7\nR8 B8 O8 K8");
+        let Some(Decision::Play(rack, face_up)) = press(&mut screen, "d") else {
+            panic!("Turn should be valid");
+        };
+        assert!(rack.is_empty());
+        assert_eq!(face_up, "B3-B7 | R8 B8 O8 K8".parse().unwrap());
+    }
+
+    #[test]
+    fn mark_and_place_a_new_set() {
+        let mut screen = screen("[R10 R11 R12 K1]", "", false);
+        assert_eq!(press(&mut screen, "d"), None);
+        assert!(screen.message.contains("No tiles were placed"));
+        press(&mut screen, "\n");
+        assert!(screen.message.contains("R10 does not fit anywhere"));
+        press(&mut screen, " >>> p");
+        assert!(screen.message.contains("Mark the tiles"));
+        press(&mut screen, "!<<< > > p");
+        assert_eq!(format!("{:#}", screen.turn.rack()), "[K1]");
+        assert!(matches!(press(&mut screen, "d"), Some(Decision::Play(..))));
+    }
+
+    #[test]
+    fn place_a_run_with_a_joker_in_the_gap() {
+        let mut screen = screen("[R3 J R1 K5]", "", true);
+        // Shown as R1 R3 K5 J, mark all but K5
+        press(&mut screen, " > >> p");
+        assert_eq!(format!("{:#}", screen.turn.face_up()), "R1 J R3");
+        // Both jokers, one in the gap and one on top
+        let marked = "[J R4 J R2]".parse::<Rack>().unwrap().rack;
+        let set = marked_set(marked).unwrap();
+        assert_eq!(format!("{:#}", set), "R2 J R4 J");
+    }
+
+    #[test]
+    fn sort_undo_and_draw() {
+        let mut screen = screen("[R2 B1 J]", "", false);
+        assert_eq!(
+            screen.rack_view(),
+            "[R2 B1 J]".parse::<Rack>().unwrap().rack
+        );
+        press(&mut screen, "s");
+        assert_eq!(
+            screen.rack_view(),
+            "[B1 R2 J]".parse::<Rack>().unwrap().rack
+        );
+        press(&mut screen, "u");
+        assert_eq!(screen.message, "Nothing left to undo");
+        assert_eq!(press(&mut screen, "x"), Some(Decision::Draw));
+    }
+
+    #[test]
+    fn shows_every_pane() {
+        let mut screen = screen("[B3 B8]", "B4-B6 | R8 O8 K8", true);
+        let shown = rendered(&screen);
+        assert!(shown.contains("#0  B4 B5 B6"));
+        assert!(shown.contains("#1  R8 O8 K8"));
+        assert!(shown.contains("Boneyard: 106 tiles"));
+        assert!(shown.contains("1 drew R5"));
+        assert!(shown.contains("Your Rack, by color (2 tiles)"));
+        press(&mut screen, ">\n");
+        let shown = rendered(&screen);
+        assert!(shown.contains("Holding B8"));
+        assert!(shown.contains("R8 O8 K8 ◀ here"));
+    }
+}
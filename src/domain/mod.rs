@@ -2,6 +2,7 @@ use crate::domain::score_value::{ScoreValue, ScoringRule};
 use crate::domain::tiles::Tile;
 use tiles::tile_sequence::TileSequence;
 
+pub mod notation;
 pub mod player;
 pub mod score_value;
 pub mod sets;
@@ -0,0 +1,332 @@
+//! A short, typable notation for tiles, sets, racks and tables, handy for tests and for playing.
+//!
+//! A tile is its color letter then its number: R for Red, B for Blue, O for Orange, K for Black,
+//! so `R5` or `K13`. The joker is just `J`.
+//! A set is its tiles separated by spaces, like the group `R5 B5 K5` or the run `O3 J O5`.
+//! A run without jokers can also be written as a range, `O3-O7`.
+//! Since `R5 J J` could be either, it is read as a run, and the group is written `group R5 J J`.
+//! A rack is its tiles in square brackets, `[R1 R2 J]`, and a table is one set per line
+//! (or sets separated by `|`).
+//!
+//! Every type prints in this notation with the alternate flag, `format!("{:#}", tile)`,
+//! while the plain `{}` stays the colored version for the terminal.
+
+use crate::domain::player::rack::Rack;
+use crate::domain::sets::group::Group;
+use crate::domain::sets::run::Run;
+use crate::domain::sets::Set;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::tiles::color::Color;
+use crate::domain::tiles::number::Number;
+use crate::domain::tiles::Tile;
+use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+use crate::domain::Decompose;
+use std::fmt;
+use std::fmt::Formatter;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+/// Why some text could not be read as a tile, set, rack or table
+#[derive(Debug, Clone, PartialEq)]
+pub struct NotationError {
+    pub text: String,
+    pub reason: &'static str,
+}
+
+impl NotationError {
+    fn of(text: &str, reason: &'static str) -> NotationError {
+        NotationError {
+            text: text.to_string(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Could not read '{}': {}", self.text, self.reason)
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+fn color_letter(color: Color) -> char {
+    match color {
+        Color::Red => 'R',
+        Color::Blue => 'B',
+        Color::Orange => 'O',
+        Color::Black => 'K',
+    }
+}
+
+fn color_of_letter(letter: char) -> Option<Color> {
+    Color::iter().find(|&c| color_letter(c) == letter.to_ascii_uppercase())
+}
+
+pub(crate) fn write_tile(f: &mut Formatter<'_>, tile: &Tile) -> fmt::Result {
+    match tile {
+        JokersWild => write!(f, "J"),
+        RegularTile(color, num) => write!(f, "{}{}", color_letter(*color), num.as_value()),
+    }
+}
+
+/// Writes a tile in the middle of a sentence, colored as usual, or in this notation with {:#}.
+/// Either way it is followed by a space
+pub(crate) fn write_tile_in_text(f: &mut Formatter<'_>, tile: &Tile) -> fmt::Result {
+    if f.alternate() {
+        write_tile(f, tile)?;
+        write!(f, " ")
+    } else {
+        write!(f, "{}", tile)
+    }
+}
+
+fn write_tiles(f: &mut Formatter<'_>, tiles: &[Tile]) -> fmt::Result {
+    for (i, tile) in tiles.iter().enumerate() {
+        if i > 0 {
+            write!(f, " ")?;
+        }
+        write_tile(f, tile)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn write_set(f: &mut Formatter<'_>, set: &Set) -> fmt::Result {
+    match set {
+        Set::Run(run) => {
+            let tiles = run.decompose();
+            match (tiles.first(), tiles.last()) {
+                (Some(first), Some(last)) if !tiles.iter().any(|t| t.is_joker()) => {
+                    write_tile(f, first)?;
+                    write!(f, "-")?;
+                    write_tile(f, last)
+                }
+                _ => write_tiles(f, &tiles),
+            }
+        }
+        Set::Group(group) => {
+            // Regular tiles first in color order, then the jokers
+            let mut tiles = group.decompose();
+            tiles.sort_by_key(|t| (t.is_joker(), *t));
+            if Run::parse(&tiles).is_some() {
+                write!(f, "group ")?;
+            }
+            write_tiles(f, &tiles)
+        }
+    }
+}
+
+pub(crate) fn write_rack(f: &mut Formatter<'_>, rack: &Rack) -> fmt::Result {
+    write!(f, "[")?;
+    write_tiles(f, &rack.rack)?;
+    write!(f, "]")
+}
+
+pub(crate) fn write_face_up(f: &mut Formatter<'_>, face_up: &FaceUpTiles) -> fmt::Result {
+    for (i, set) in face_up.sets.iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+        }
+        write_set(f, set)?;
+    }
+    Ok(())
+}
+
+impl FromStr for Tile {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if text.eq_ignore_ascii_case("J") {
+            return Ok(JokersWild);
+        }
+        let mut chars = text.chars();
+        let color = chars
+            .next()
+            .and_then(color_of_letter)
+            .ok_or_else(|| NotationError::of(text, "tiles start with R, B, O, K or are J"))?;
+        let number = chars
+            .as_str()
+            .parse::<u16>()
+            .ok()
+            .and_then(|n| Number::iter().find(|num| num.as_value().as_u16() == n))
+            .ok_or_else(|| NotationError::of(text, "numbers go from 1 to 13"))?;
+        Ok(RegularTile(color, number))
+    }
+}
+
+/// Reads space separated tiles, where any run of a single color can be written as a range
+fn parse_tiles(text: &str) -> Result<Vec<Tile>, NotationError> {
+    let mut tiles = vec![];
+    for word in text.split_whitespace() {
+        match word.split_once('-') {
+            Some((from, to)) => match (from.parse::<Tile>()?, to.parse::<Tile>()?) {
+                (RegularTile(color, low), RegularTile(to_color, high))
+                    if color == to_color && low < high =>
+                {
+                    tiles.extend(
+                        Number::iter()
+                            .filter(|n| low <= *n && *n <= high)
+                            .map(|n| RegularTile(color, n)),
+                    )
+                }
+                _ => {
+                    return Err(NotationError::of(
+                        word,
+                        "ranges go from low to high, in one color",
+                    ))
+                }
+            },
+            None => tiles.push(word.parse()?),
+        }
+    }
+    Ok(tiles)
+}
+
+impl FromStr for Set {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        if let Some(group) = text.strip_prefix("group ") {
+            return Group::parse(parse_tiles(group)?)
+                .map(Set::Group)
+                .ok_or_else(|| NotationError::of(text, "not a valid group"));
+        }
+        let tiles = parse_tiles(text)?;
+        if let Some(run) = Run::parse(&tiles) {
+            return Ok(Set::Run(run));
+        }
+        Group::parse(tiles)
+            .map(Set::Group)
+            .ok_or_else(|| NotationError::of(text, "neither a valid run nor a valid group"))
+    }
+}
+
+/// A rack read from text has not played its initial meld yet
+impl FromStr for Rack {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let inside = text
+            .strip_prefix('[')
+            .and_then(|t| t.strip_suffix(']'))
+            .unwrap_or(text);
+        Ok(Rack {
+            rack: parse_tiles(inside)?,
+            played_initial_meld: false,
+        })
+    }
+}
+
+impl FromStr for FaceUpTiles {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sets = s
+            .split(['\n', '|'])
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.parse())
+            .collect::<Result<Vec<Set>, NotationError>>()?;
+        Ok(FaceUpTiles { sets })
+    }
+}
+
+#[cfg(test)]
+mod notation_tests {
+    use super::*;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+
+    #[test]
+    fn tiles() {
+        assert_eq!("R5".parse(), Ok(RegularTile(Red, Five)));
+        assert_eq!("k13".parse(), Ok(RegularTile(Black, Thirteen)));
+        assert_eq!("O1".parse(), Ok(RegularTile(Orange, One)));
+        assert_eq!("J".parse(), Ok(JokersWild));
+        assert!("G5".parse::<Tile>().is_err());
+        assert!("R14".parse::<Tile>().is_err());
+        assert!("R".parse::<Tile>().is_err());
+        assert_eq!(format!("{:#}", RegularTile(Blue, Ten)), "B10");
+        assert_eq!(format!("{:#}", JokersWild), "J");
+    }
+
+    #[test]
+    fn runs_and_groups() {
+        let group: Set = "R5 B5 K5".parse().unwrap();
+        assert_eq!(
+            group,
+            Set::Group(Group::of(Five, &vec![Red, Blue, Black]).unwrap())
+        );
+        assert_eq!(format!("{:#}", group), "R5 B5 K5");
+
+        let run: Set = "O3-O7".parse().unwrap();
+        assert_eq!(run, Set::Run(Run::of(Three, Orange, 5).unwrap()));
+        assert_eq!(format!("{:#}", run), "O3-O7");
+
+        let with_joker: Set = "O3 J O5".parse().unwrap();
+        assert_eq!(format!("{:#}", with_joker), "O3 J O5");
+        assert_eq!("O3-O4 J".parse::<Set>(), "O3 O4 J".parse::<Set>());
+
+        assert!("R5 B6 K5".parse::<Set>().is_err());
+        assert!("O7-O3".parse::<Set>().is_err());
+        assert!("R3-B5".parse::<Set>().is_err());
+    }
+
+    /// One tile and two jokers is both a run and a group, so the group has to say so
+    #[test]
+    fn ambiguous_group_is_marked() {
+        let run: Set = "R5 J J".parse().unwrap();
+        assert!(matches!(run, Set::Run(_)));
+        let group: Set = "group R5 J J".parse().unwrap();
+        assert!(matches!(group, Set::Group(_)));
+        assert_eq!(format!("{:#}", group), "group R5 J J");
+        assert_eq!(format!("{:#}", run), "R5 J J");
+    }
+
+    #[test]
+    fn racks_and_tables() {
+        let rack: Rack = "[R1 R2 J]".parse().unwrap();
+        assert_eq!(
+            rack.rack,
+            vec![RegularTile(Red, One), RegularTile(Red, Two), JokersWild]
+        );
+        assert_eq!(format!("{:#}", rack), "[R1 R2 J]");
+        assert_eq!("[]".parse::<Rack>().unwrap().rack, vec![]);
+
+        let table: FaceUpTiles = "R5 B5 K5 | O3-O7\nB9 J B11".parse().unwrap();
+        assert_eq!(table.sets.len(), 3);
+        assert_eq!(format!("{:#}", table), "R5 B5 K5\nO3-O7\nB9 J B11");
+        assert_eq!(format!("{:#}", table).parse::<FaceUpTiles>(), Ok(table));
+        assert!("R5 B5 K5 | R1".parse::<FaceUpTiles>().is_err());
+    }
+
+    /// Whatever is printed can be read back again, for every rack and table of a real game
+    #[test]
+    fn round_trips_a_whole_game() {
+        use crate::game_loop::events::{replay, GameEvent};
+        use crate::game_loop::main_game_loop_with_events;
+        use crate::game_loop::meta::GameConfig;
+        use crate::game_loop::state::GameState;
+
+        let start = GameState::init_game(GameConfig {
+            num_players: 4,
+            seed: Some(31),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        main_game_loop_with_events(start.clone(), &mut events);
+        for state in replay(&start, &events).unwrap() {
+            let table = &state.table.face_up;
+            assert_eq!(
+                format!("{:#}", table).parse::<FaceUpTiles>().as_ref(),
+                Ok(table)
+            );
+            for player in state.players {
+                let read: Rack = format!("{:#}", player.rack).parse().unwrap();
+                assert_eq!(read.rack, player.rack.rack);
+            }
+        }
+    }
+}
@@ -1,10 +1,11 @@
+use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
 
 /// Information about the player, like name, and or someday maybe
 /// difficulty, or ranking etc.
 /// Also uses fancy Tuple struct with a str reference and static lifetime
 /// essentially making this type barely a wrapper, which indeed it is
-#[derive(Debug, Clone, PartialOrd, PartialEq, Ord, Eq)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Ord, Eq, Serialize, Deserialize)]
 pub struct PlayerInfo(String);
 
 impl std::fmt::Display for PlayerInfo {
@@ -1,9 +1,9 @@
 use crate::domain::score_value::ScoreValue;
-use crate::domain::score_value::ScoringRule::OnRack;
+use crate::domain::score_value::ScoringRule::OnTable;
 use crate::domain::sets::Set;
 use crate::domain::Decompose;
 
-const MINIMUM_MELD_SCORE: ScoreValue = ScoreValue::of_u16(30u16);
+pub const MINIMUM_MELD_SCORE: ScoreValue = ScoreValue::of_u16(30u16);
 
 /// In order to make an initial meld, each player must place tiles in one or more sets that total at least 30 points.
 /// These points must come from the tiles on each player’s rack only.
@@ -19,11 +19,11 @@ impl InitialMeld {
         let mut score_sum = ScoreValue::of_u16(0u16);
         for set in &candidates {
             match set {
-                Set::Group(g) => score_sum += g.score(OnRack).ok()?,
-                Set::Run(r) => score_sum += r.score(OnRack).ok()?,
+                Set::Group(g) => score_sum += g.score(OnTable).ok()?,
+                Set::Run(r) => score_sum += r.score(OnTable).ok()?,
             }
         }
-        if score_sum > MINIMUM_MELD_SCORE {
+        if score_sum >= MINIMUM_MELD_SCORE {
             return Some(InitialMeld {
                 sets: candidates.clone(),
             });
@@ -31,3 +31,39 @@ impl InitialMeld {
         None
     }
 }
+
+#[cfg(test)]
+mod initial_meld_tests {
+    use super::*;
+    use crate::domain::sets::group::Group;
+    use crate::domain::sets::run::Run;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+
+    #[test]
+    fn exactly_thirty_is_enough() {
+        let tens = Group::of(Ten, &vec![Red, Blue, Black]).unwrap();
+        assert!(InitialMeld::parse(vec![Set::Group(tens)]).is_some());
+
+        let nines = Group::of(Nine, &vec![Red, Blue, Black]).unwrap();
+        assert!(InitialMeld::parse(vec![Set::Group(nines)]).is_none());
+    }
+
+    /// On the rack a joker would be worth 30 on its own, but in a meld it is only worth the
+    /// tile it stands for
+    #[test]
+    fn joker_scores_the_tile_it_represents() {
+        let with_joker =
+            Run::parse(&[RegularTile(Red, One), RegularTile(Red, Two), JokersWild]).unwrap();
+        assert!(InitialMeld::parse(vec![Set::Run(with_joker)]).is_none());
+
+        let tens = Group::parse(vec![
+            RegularTile(Red, Ten),
+            RegularTile(Blue, Ten),
+            JokersWild,
+        ])
+        .unwrap();
+        assert!(InitialMeld::parse(vec![Set::Group(tens)]).is_some());
+    }
+}
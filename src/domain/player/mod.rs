@@ -1,12 +1,44 @@
 use crate::domain::player::info::PlayerInfo;
 use crate::domain::player::rack::Rack;
+use crate::game_loop::strategy::Strategy;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::sync::Arc;
 
 pub mod info;
 pub mod initial_meld;
 pub mod rack;
 
-#[derive(Debug, Clone, PartialOrd, PartialEq, Ord, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub info: PlayerInfo,
     pub rack: Rack,
+    /// How this player chooses to play each turn
+    #[serde(with = "crate::game_loop::strategy::by_name")]
+    pub strategy: Arc<dyn Strategy>,
+}
+
+/// Players are compared by who they are and what they hold, strategies only by their name
+impl PartialEq for Player {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Player {}
+
+impl PartialOrd for Player {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Player {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.info, &self.rack, self.strategy.name()).cmp(&(
+            &other.info,
+            &other.rack,
+            other.strategy.name(),
+        ))
+    }
 }
@@ -1,5 +1,5 @@
 use std::borrow::Borrow;
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
 
@@ -13,7 +13,8 @@ use crate::domain::table::boneyard::Boneyard;
 use crate::domain::tiles::number::Number;
 use crate::domain::tiles::tile_sequence::{only_regular_tiles, TileSequence, TileSequenceType};
 use crate::domain::tiles::Tile;
-use crate::domain::{Decompose, RummikubError};
+use crate::domain::{notation, Decompose, RummikubError};
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use ScoringRule::OnTable;
 
@@ -22,7 +23,7 @@ const INITIAL_TILES: u8 = 14;
 /// Player racks can hold any number of tiles (up to all tiles not had by other players)
 /// This information is known only to the owning player
 /// Cannot derive copy trait because Vec uses heap memory which prevents bitwise copy
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Rack {
     pub rack: Vec<Tile>, // TODO make this private, create a nicer constructor
     pub played_initial_meld: bool,
@@ -30,6 +31,9 @@ pub struct Rack {
 
 impl Display for Rack {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return notation::write_rack(f, self);
+        }
         write!(f, "[ ").unwrap();
         for t in &self.rack {
             write!(f, "{}", t).unwrap()
@@ -116,12 +120,15 @@ impl Rack {
 
     /// Returns all available sets that currently exist on the rack.
     /// Prefers Runs before Groups, so if a tile is needed in a run, it won't be re-used in a possible group
+    /// Jokers are held back until every set without them is found, and then each one goes to
+    /// whichever set completes the most of the leftover tiles.
     /// TODO Hey, couldn't you just mix the rack and the table and perform this same algorithm? (kinda)
     pub fn sets_on_rack(&self) -> Option<(Vec<Set>, Rack)> {
         let mut sets: Vec<Set> = vec![];
-        let mut new_rack = self.clone();
+        let jokers: TileSequence = self.rack.iter().filter(|t| t.is_joker()).cloned().collect();
+        let mut new_rack = self.remove(&jokers).expect("Jokers came from the rack");
 
-        let mut optional_run = self.get_largest_run();
+        let mut optional_run = new_rack.get_largest_run();
         while let Some(ref largest_run) = optional_run {
             new_rack = new_rack
                 .remove(largest_run)
@@ -137,6 +144,17 @@ impl Rack {
                 .remove(&g)
                 .expect("Unable to remove set from rack, which claims it exists!");
         }
+
+        for joker in &jokers {
+            new_rack.add_tile_to_rack(joker);
+        }
+        while let Some(joker_set) = new_rack.best_use_of_jokers() {
+            new_rack = new_rack
+                .remove(&joker_set)
+                .expect("Unable to remove set from rack, which claims it exists!");
+            sets.push(joker_set);
+        }
+
         return if sets.len() == 0 {
             None
         } else {
@@ -144,8 +162,30 @@ impl Rack {
         };
     }
 
+    /// Finds the set containing a joker that places the most regular tiles from the rack,
+    /// then the fewest jokers, then the highest value
+    fn best_use_of_jokers(&self) -> Option<Set> {
+        let tiles = TileSequenceType::of(self);
+        if tiles.jokers() == 0 {
+            return None;
+        }
+        let runs = tiles.all_runs().into_iter().map(Set::Run);
+        let groups = tiles.all_groups().into_iter().map(Set::Group);
+        runs.chain(groups)
+            .map(|set| {
+                let jokers = set.decompose().iter().filter(|t| t.is_joker()).count();
+                let regular = set.decompose().len() - jokers;
+                (set, regular, jokers)
+            })
+            .filter(|(_, _, jokers)| *jokers > 0)
+            .max_by_key(|(set, regular, jokers)| {
+                (*regular, Reverse(*jokers), set.score(OnTable).ok())
+            })
+            .map(|(set, _, _)| set)
+    }
+
     /// Returns all Groups that are possible to create given the tiles currently present on the rack
-    /// TODO Current Implementation Ignores Jokers
+    /// Jokers are used to complete groups, as many as remain on the rack
     pub fn groups_on_rack(&self) -> Vec<Group> {
         let mut remaining = TileSequenceType::of(self);
         let mut groups: Vec<Group> = vec![];
@@ -191,7 +231,7 @@ impl Rack {
 #[cfg(test)]
 mod basic_tests {
     use crate::domain::player::rack::Rack;
-    use crate::domain::score_value::ScoringRule::OnRack;
+    use crate::domain::score_value::ScoringRule::{OnRack, OnTable};
     use crate::domain::score_value::{ScoreValue, JOKER_RACK_SCORE};
     use crate::domain::sets::group::Group;
     use crate::domain::sets::run::Run;
@@ -371,4 +411,61 @@ mod basic_tests {
         );
         assert_eq!(Count(5), test_rack.count().expect("Rack must be countable"))
     }
+
+    #[test]
+    pub fn jokers_complete_sets_on_rack() {
+        let test_rack = Rack {
+            rack: vec![
+                RegularTile(Blue, Five),
+                RegularTile(Blue, Seven),
+                RegularTile(Red, Twelve),
+                RegularTile(Black, Twelve),
+                JokersWild,
+                JokersWild,
+            ],
+            played_initial_meld: false,
+        };
+        let (found_sets, remaining) = test_rack.sets_on_rack().expect("Jokers make two sets");
+        assert!(remaining.is_empty());
+        let with_gap = Run::parse(&[
+            RegularTile(Blue, Five),
+            JokersWild,
+            RegularTile(Blue, Seven),
+        ])
+        .unwrap();
+        let twelves = Group::parse(vec![
+            RegularTile(Red, Twelve),
+            RegularTile(Black, Twelve),
+            JokersWild,
+        ])
+        .unwrap();
+        assert_eq!(found_sets, vec![Set::Group(twelves), Set::Run(with_gap)]);
+    }
+
+    /// Ten, Eleven and a joker standing in for Twelve is 33, enough to meld
+    #[test]
+    pub fn joker_counts_towards_initial_meld() {
+        let test_rack = Rack {
+            rack: vec![
+                RegularTile(Orange, Ten),
+                RegularTile(Orange, Eleven),
+                JokersWild,
+                RegularTile(Red, One),
+            ],
+            played_initial_meld: false,
+        };
+        let meld = test_rack.can_play_initial_meld().expect("Joker completes meld");
+        let run = Run::parse(&[
+            RegularTile(Orange, Ten),
+            RegularTile(Orange, Eleven),
+            JokersWild,
+        ])
+        .unwrap();
+        assert_eq!(meld.sets, vec![Set::Run(run)]);
+        assert_eq!(meld.sets[0].score(OnTable).unwrap(), ScoreValue::of_u16(33));
+
+        // Without the joker, no sets at all
+        let no_joker = test_rack.remove(&JokersWild).unwrap();
+        assert!(no_joker.can_play_initial_meld().is_none());
+    }
 }
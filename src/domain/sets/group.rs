@@ -5,6 +5,8 @@ use crate::domain::tiles::number::Number;
 use crate::domain::tiles::Tile;
 use crate::domain::tiles::Tile::RegularTile;
 use crate::domain::{Count, Decompose, RummikubError};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashSet;
 use std::fmt::Display;
 use ScoringRule::OnTable;
@@ -12,7 +14,8 @@ use Tile::JokersWild;
 
 const MAX_GROUP_SIZE: usize = 4;
 const MIN_GROUP_SIZE: usize = 3;
-const MAX_JOKERS_IN_GROUP: u8 = 2;
+/// As per the rules, This is the maximum quantity of jokers that can exist in a single group
+pub const MAX_JOKERS_IN_GROUP: u8 = 2;
 
 ///A set of either three or four tiles of the same number in different colors.
 #[derive(Debug, PartialEq, Clone)]
@@ -152,7 +155,37 @@ impl Group {
     /// with a regular tile that forms a valid Group. Either Color in a Group of 3 is acceptable
     /// If successful returns the new run and a Joker Tile
     pub fn retrieve_joker(&self, tile: Tile) -> Option<(Group, Tile)> {
-        todo!()
+        let RegularTile(color, num) = tile else {
+            return None;
+        };
+        if self.jokers == 0 || num != self.num || self.contains(color) {
+            return None;
+        }
+        let mut colors = self.colors.clone();
+        colors.insert(color);
+        let new_group = Group {
+            num: self.num,
+            colors,
+            jokers: self.jokers - 1,
+        };
+        Some((new_group, JokersWild))
+    }
+}
+
+/// Saved as its tiles, and parsed again when loaded, so an invalid group can never be loaded.
+/// The tiles are sorted, so the same group is always saved the same way
+impl Serialize for Group {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tiles = self.decompose();
+        tiles.sort();
+        tiles.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Group {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tiles = Vec::<Tile>::deserialize(deserializer)?;
+        Group::parse(tiles).ok_or_else(|| D::Error::custom("tiles are not a valid group"))
     }
 }
 
@@ -378,4 +411,32 @@ pub mod group_tests {
         ));
         assert_eq!(gang_of_four_joker.extract_spare(Blue), expected);
     }
+
+    #[test]
+    fn test_retrieve_joker() {
+        let joker_group = Group::parse(vec![
+            RegularTile(Red, Three),
+            RegularTile(Blue, Three),
+            JokersWild,
+        ])
+        .unwrap();
+        // Either of the missing colors will do
+        for color in [Black, Orange] {
+            let (group, joker) = joker_group
+                .retrieve_joker(RegularTile(color, Three))
+                .expect("Missing color must replace the joker");
+            assert_eq!(joker, JokersWild);
+            assert_eq!(group, Group::of(Three, &vec![Red, Blue, color]).unwrap());
+        }
+        assert!(joker_group
+            .retrieve_joker(RegularTile(Red, Three))
+            .is_none());
+        assert!(joker_group
+            .retrieve_joker(RegularTile(Black, Four))
+            .is_none());
+        assert!(joker_group.retrieve_joker(JokersWild).is_none());
+
+        let no_joker = Group::of(Three, &vec![Red, Blue, Orange]).unwrap();
+        assert!(no_joker.retrieve_joker(RegularTile(Black, Three)).is_none());
+    }
 }
@@ -5,12 +5,13 @@ use run::Run;
 
 use crate::domain::score_value::{ScoreValue, ScoringRule};
 use crate::domain::tiles::Tile;
-use crate::domain::{Decompose, RummikubError};
+use crate::domain::{notation, Decompose, RummikubError};
+use serde::{Deserialize, Serialize};
 
 pub mod group;
 pub mod run;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Set {
     // There are two kinds of sets, either a group or a run
     Group(Group),
@@ -19,6 +20,9 @@ pub enum Set {
 
 impl Display for Set {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return notation::write_set(f, self);
+        }
         let tiles = self.decompose();
         let whoopsie = format!("EXPLODED writing: {:?}", tiles);
         write!(f, "|").expect("Literally unfailable");
@@ -47,6 +51,17 @@ impl Decompose for Set {
     }
 }
 
+impl Set {
+    /// Checks the set still holds up against the rules, by parsing its own tiles again.
+    /// A set which has been manipulated into something that is not a Run or Group is invalid
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Set::Group(g) => Group::parse(g.decompose()).as_ref() == Some(g),
+            Set::Run(r) => Run::parse(&r.decompose()).as_ref() == Some(r),
+        }
+    }
+}
+
 /// Certain types of erros that can occur when attempting to parse a collection of tiles
 /// into a particular type of Set
 #[derive(PartialEq, Debug)]
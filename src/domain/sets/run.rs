@@ -7,6 +7,8 @@ use crate::domain::tiles::tile_sequence::{unique_colors, TileSequence};
 use crate::domain::tiles::Tile;
 use crate::domain::tiles::Tile::{JokersWild, RegularTile};
 use crate::domain::{Decompose, RummikubError};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::vec;
 use ScoringRule::OnTable;
@@ -44,7 +46,7 @@ const MIN_NATURAL_RUN_SPLIT_SIZE: usize = MIN_RUN_SIZE * 2;
 /// The minimum size for creating two runs by adding one tile, is always double the min minus one
 const MIN_WEDGE_RUN_SPLIT_SIZE: usize = MIN_RUN_SIZE * 2 - 1;
 /// As per the rules, This is the maximum quantity of jokers that can exist in a single run
-const MAX_JOKERS_IN_RUN: usize = 2;
+pub const MAX_JOKERS_IN_RUN: usize = 2;
 
 impl Run {
     /// Creates a run based on defining parameters as given in constructor
@@ -225,7 +227,8 @@ impl Run {
         if tiles.len() <= position {
             return None;
         }
-        if !wedge.is_joker() && tiles[position] != wedge {
+        // A joker standing in for the wedge number stays with the greater run, in place of it
+        if !wedge.is_joker() && !tiles[position].is_joker() && tiles[position] != wedge {
             return None;
         }
         let (left, right) = tiles.split_at(position);
@@ -372,7 +375,32 @@ impl Run {
     /// with a regular tile that forms a valid run
     /// If successful returns the new run and a Joker Tile
     pub fn retrieve_joker(&self, tile: Tile) -> Option<(Run, Tile)> {
-        todo!()
+        let RegularTile(color, num) = tile else {
+            return None;
+        };
+        if color != self.color || !self.jokers.contains(&num) {
+            return None;
+        }
+        let mut new_run = self.clone();
+        new_run.jokers.remove(&num);
+        Some((new_run, JokersWild))
+    }
+
+    /// A joker can also be freed without replacing it, if the run can be split on either side
+    /// of the joker and leave two valid runs behind, i.e. [1,2,3,J,5,6,7] -> [1,2,3], J, [5,6,7]
+    /// If successful returns the lesser and greater runs, and the freed Joker Tile
+    pub fn split_freeing_joker(&self) -> Option<(Run, Run, Tile)> {
+        let tiles = self.decompose();
+        for (pos, tile) in tiles.iter().enumerate() {
+            if !tile.is_joker() {
+                continue;
+            }
+            let (left, right) = tiles.split_at(pos);
+            if let (Some(lesser), Some(greater)) = (Run::parse(left), Run::parse(&right[1..])) {
+                return Some((lesser, greater, JokersWild));
+            }
+        }
+        None
     }
 
     /// takes a candidate tile. If it is possible and allowed to be added returns a NEW run
@@ -476,6 +504,21 @@ impl Run {
     }
 }
 
+/// Saved as its tiles from left to right, so every joker keeps its position.
+/// Loading parses the tiles again, so an invalid run can never be loaded
+impl Serialize for Run {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.decompose().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Run {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let tiles = Vec::<Tile>::deserialize(deserializer)?;
+        Run::parse(&tiles).ok_or_else(|| D::Error::custom("tiles are not a valid run"))
+    }
+}
+
 impl Decompose for Run {
     fn decompose(&self) -> Vec<Tile> {
         // Whoa ->  the power of vector implementations
@@ -1289,4 +1332,77 @@ mod other_tests_of_runs {
         remaining.jokers.insert(Four);
         assert_eq!(with_joke.all_spares(Right, 2), Some((expected, remaining)));
     }
+
+    #[test]
+    pub fn manipulated_run_is_no_longer_a_valid_set() {
+        use crate::domain::sets::Set;
+        let mut run = Run::of(One, Blue, 4).unwrap();
+        assert!(Set::Run(run.clone()).is_valid());
+        run.jokers.insert(One);
+        run.jokers.insert(Two);
+        assert!(Set::Run(run.clone()).is_valid());
+        run.jokers.insert(Three);
+        assert!(!Set::Run(run).is_valid());
+    }
+
+    #[test]
+    pub fn test_retrieve_joker() {
+        let with_joker = Run::parse(&[
+            RegularTile(Blue, Three),
+            RegularTile(Blue, Four),
+            JokersWild,
+            RegularTile(Blue, Six),
+        ])
+        .unwrap();
+        let expected = (Run::of(Three, Blue, 4).unwrap(), JokersWild);
+        assert_eq!(
+            with_joker.retrieve_joker(RegularTile(Blue, Five)),
+            Some(expected)
+        );
+        assert!(with_joker.retrieve_joker(RegularTile(Red, Five)).is_none());
+        assert!(with_joker.retrieve_joker(RegularTile(Blue, Four)).is_none());
+        assert!(with_joker.retrieve_joker(JokersWild).is_none());
+        assert!(Run::of(Three, Blue, 4)
+            .unwrap()
+            .retrieve_joker(RegularTile(Blue, Five))
+            .is_none());
+    }
+
+    #[test]
+    pub fn test_split_freeing_joker() {
+        let mut one_thru_seven = Run::of(One, Red, 7).unwrap();
+        assert!(one_thru_seven.split_freeing_joker().is_none());
+
+        one_thru_seven.jokers.insert(Four);
+        let expected = (
+            Run::of(One, Red, 3).unwrap(),
+            Run::of(Five, Red, 3).unwrap(),
+            JokersWild,
+        );
+        assert_eq!(one_thru_seven.split_freeing_joker(), Some(expected));
+
+        // Too close to the edge, one side would be too short
+        let mut one_thru_six = Run::of(One, Red, 6).unwrap();
+        one_thru_six.jokers.insert(Four);
+        assert!(one_thru_six.split_freeing_joker().is_none());
+    }
+
+    #[test]
+    pub fn test_insert_wedge_where_joker_stands_in() {
+        let mut one_thru_seven = Run::of(One, Red, 7).unwrap();
+        one_thru_seven.jokers.insert(Four);
+        let wedge = RegularTile(Red, Four);
+        let slot = one_thru_seven.all_possible_slots().unwrap()[&wedge];
+
+        let (lesser, greater) = one_thru_seven.insert_tile(wedge, slot).unwrap();
+        assert_eq!(lesser, Run::of(One, Red, 4).unwrap());
+        let expected = Run::parse(&[
+            JokersWild,
+            RegularTile(Red, Five),
+            RegularTile(Red, Six),
+            RegularTile(Red, Seven),
+        ])
+        .unwrap();
+        assert_eq!(greater, Some(expected));
+    }
 }
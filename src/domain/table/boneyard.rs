@@ -3,11 +3,14 @@ use crate::domain::tiles::number::Number;
 use crate::domain::tiles::Tile::{JokersWild, RegularTile};
 use crate::domain::tiles::*;
 use rand::prelude::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
 use strum::IntoEnumIterator;
 
 ///Starts with 106 tiles (8 sets of tiles 1-13 in four colours (2 of each), and 2 joker tiles)
-#[derive(Debug, Clone, PartialEq)]
+/// Saved in order, so a loaded game draws exactly the same tiles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Boneyard {
     pub bones: Vec<Tile>,
 }
@@ -20,6 +23,19 @@ impl std::fmt::Display for Boneyard {
 
 impl Boneyard {
     pub fn new_game() -> Self {
+        Boneyard::new_game_from(&mut rand::thread_rng())
+    }
+
+    /// Creates the full set of tiles, shuffled by the provided generator.
+    /// Given the same seeded generator, the draw order will always be the same
+    pub fn new_game_from(rng: &mut impl Rng) -> Self {
+        let mut tiles = Boneyard::unshuffled().bones;
+        tiles.shuffle(rng);
+        Boneyard { bones: tiles }
+    }
+
+    /// The full set of tiles, jokers first and then each colour in order
+    pub fn unshuffled() -> Self {
         let mut tiles = vec![JokersWild, JokersWild]; // tradeoffs vs push push?
         for color in Color::iter() {
             for num in Number::iter() {
@@ -27,7 +43,6 @@ impl Boneyard {
                 tiles.push(RegularTile(color, num));
             }
         }
-        tiles.shuffle(&mut rand::thread_rng());
         Boneyard { bones: tiles }
     }
 
@@ -80,4 +95,15 @@ mod test_boneyard {
         let new = new_bones.bones.len();
         assert_ne!(old, new)
     }
+
+    #[test]
+    fn same_seed_same_shuffle() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha8Rng;
+        let first = Boneyard::new_game_from(&mut ChaCha8Rng::seed_from_u64(42));
+        let second = Boneyard::new_game_from(&mut ChaCha8Rng::seed_from_u64(42));
+        let other = Boneyard::new_game_from(&mut ChaCha8Rng::seed_from_u64(7));
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+    }
 }
@@ -1,5 +1,7 @@
+use crate::domain::notation;
 use crate::domain::sets::Set;
 use crate::domain::tiles::Tile;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
@@ -8,7 +10,7 @@ use std::fmt::{Display, Formatter};
 /// and or add/determine the difference with a single new tile.
 pub struct Layout;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FaceUpTiles {
     // Publicly viewable and mutable by all players, has all the sets that have been placed
     pub sets: Vec<Set>,
@@ -26,6 +28,9 @@ use ScoringRule::OnRack;
 
 impl Display for FaceUpTiles {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return notation::write_face_up(f, self);
+        }
         for s in &self.sets {
             write!(f, "{}\n", s)?
         }
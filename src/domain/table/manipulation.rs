@@ -1,6 +1,6 @@
 use crate::domain::player::rack::Rack;
 use crate::domain::sets::group::Group;
-use crate::domain::sets::run::Run;
+use crate::domain::sets::run::{Run, Slot};
 use crate::domain::sets::Set;
 use crate::domain::table::face_up::FaceUpTiles;
 use crate::domain::tiles::tile_sequence::{TileSequence, TileSequenceType};
@@ -9,16 +9,9 @@ use crate::domain::{Count, Decompose};
 
 /// If possible, places one (or more) tiles from the rack into the face up tiles on the table
 /// Returns the new Rack and New Tiles if successful, otherwise returns None,
-/// indicating no change could be made
-/// TODO this is the one actually called, and would be a great place to "instantiate" or provide
-/// a strategy
-pub fn rearrange(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
-    let chosen_fn: fn(&Rack, &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> = human_like_algorithm;
-
-    chosen_fn(rack, table)
-}
-
-fn human_like_algorithm(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+/// indicating no change could be made.
+/// Attempts each tile from the rack on each run, then each group, as a person might
+pub fn human_like_algorithm(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
     let mut remaining = TileSequenceType::of(&rack.decompose());
     let mut added: TileSequence = vec![];
     let mut groups: Vec<Group> = vec![];
@@ -87,7 +80,8 @@ fn human_like_algorithm(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, FaceU
     None
 }
 
-fn place_new_tiles_simple(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+/// Attempts to add every tile on the rack to the first set on the table that will accept it
+pub fn place_new_tiles_simple(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
     // Place New Tiles simple
     let mut mut_face_up = table.clone();
     let mut remaining = TileSequenceType::of(&rack.decompose());
@@ -107,11 +101,52 @@ fn place_new_tiles_simple(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, Fac
     None
 }
 
+/// Retrieves a joker from the table by swapping in the matching tile from the rack, and then
+/// immediately plays the freed joker on a different set, as it may not be kept for later.
+/// Only a single joker is retrieved, returns None if no joker could be swapped and replayed
+pub fn retrieve_and_replay_joker(rack: &Rack, table: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+    for (set_idx, set) in table.sets.iter().enumerate() {
+        for tile in rack.decompose() {
+            let swapped = match set {
+                Set::Run(run) => run
+                    .retrieve_joker(tile)
+                    .map(|(r, joker)| (Set::Run(r), joker)),
+                Set::Group(group) => group
+                    .retrieve_joker(tile)
+                    .map(|(g, joker)| (Set::Group(g), joker)),
+            };
+            let Some((swapped_set, joker)) = swapped else {
+                continue;
+            };
+
+            for (other_idx, other) in table.sets.iter().enumerate() {
+                if other_idx == set_idx {
+                    continue;
+                }
+                let replayed = match other {
+                    Set::Run(run) => run
+                        .insert_tile(joker, Slot::Right)
+                        .or_else(|| run.insert_tile(joker, Slot::Left))
+                        .map(|(r, _)| Set::Run(r)),
+                    Set::Group(group) => group.insert_tile(&joker).map(Set::Group),
+                };
+                if let Some(replayed_set) = replayed {
+                    let mut sets = table.sets.clone();
+                    sets[set_idx] = swapped_set;
+                    sets[other_idx] = replayed_set;
+                    return Some((rack.remove(&tile).ok()?, FaceUpTiles { sets }));
+                }
+            }
+        }
+    }
+    None
+}
+
 /// Simples possible version of the algorithm. If I have something decomposable (presumably the
 /// face up tiles), shatter it into it's individual components, and then attempt to
 /// put it back together again, but including the new tile.
 /// Simple implementation creates all runs from largest to smallest, and then groups
-fn shatter_and_recombobulate(
+pub fn shatter_and_recombobulate(
     candidates: &impl Decompose,
     initial_table: &impl Decompose,
 ) -> Option<(TileSequenceType, FaceUpTiles)> {
@@ -179,28 +214,14 @@ mod example_manipulation_tests_from_rulebook {
     /// group of 8’s already on the table.
     #[test]
     pub fn add_tile_to_make_new_set() {
-        let example_rack = Rack::new(
-            &vec![RegularTile(Blue, Three), RegularTile(Blue, Eight)],
-            None,
-        )
-        .expect("TEST");
-        let example_table: FaceUpTiles = FaceUpTiles {
-            sets: vec![
-                Run(Run::of(Four, Blue, 3).expect("TEST")),
-                Group(Group::of(Eight, &vec![Red, Orange, Black]).expect("TEST")),
-            ],
-        };
+        let example_rack: Rack = "[B3 B8]".parse().expect("TEST");
+        let example_table: FaceUpTiles = "B4-B6 | R8 O8 K8".parse().expect("TEST");
 
-        let actual = rearrange(&example_rack, &example_table);
+        let actual = human_like_algorithm(&example_rack, &example_table);
 
         assert!(actual.is_some());
         // TODO fix the fact that order matters
-        let expected_table = FaceUpTiles {
-            sets: vec![
-                Run(Run::of(Three, Blue, 4).expect("TEST")),
-                Group(Group::of(Eight, &vec![Red, Orange, Black, Blue]).expect("TEST")),
-            ],
-        };
+        let expected_table: FaceUpTiles = "B3-B6 | R8 B8 O8 K8".parse().expect("TEST");
         let (actual_rack, actual_table) = actual.unwrap();
         assert!(actual_rack.is_empty());
         assert_eq!(expected_table, actual_table);
@@ -231,7 +252,7 @@ mod example_manipulation_tests_from_rulebook {
                 Group::of(Four, &vec![Red, Orange, Black, Blue]).expect("TEST"),
             )],
         };
-        let actual = rearrange(&test_rack, &test_table);
+        let actual = human_like_algorithm(&test_rack, &test_table);
 
         assert!(actual.is_some());
         let (actual_rack, actual_table) = actual.expect("TEST");
@@ -256,7 +277,7 @@ mod example_manipulation_tests_from_rulebook {
             sets: vec![Run(Run::of(Eight, Blue, 3).expect("TEST"))],
         };
 
-        let actual = rearrange(&test_rack, &test_table);
+        let actual = human_like_algorithm(&test_rack, &test_table);
 
         assert!(actual.is_some());
         let (actual_rack, actual_table) = actual.expect("TEST");
@@ -279,7 +300,7 @@ mod example_manipulation_tests_from_rulebook {
             sets: vec![Run(Run::of(Four, Red, 5).expect("TEST"))],
         };
 
-        let actual = rearrange(&test_rack, &test_table);
+        let actual = human_like_algorithm(&test_rack, &test_table);
 
         assert!(actual.is_some());
         let (actual_rack, actual_table) = actual.expect("TEST");
@@ -306,7 +327,7 @@ mod example_manipulation_tests_from_rulebook {
             ],
         };
 
-        let actual = rearrange(&test_rack, &test_table);
+        let actual = human_like_algorithm(&test_rack, &test_table);
 
         assert!(actual.is_some());
         let (actual_rack, actual_table) = actual.expect("TEST");
@@ -339,7 +360,7 @@ mod example_manipulation_tests_from_rulebook {
             ],
         };
 
-        let actual = rearrange(&test_rack, &test_table);
+        let actual = human_like_algorithm(&test_rack, &test_table);
 
         assert!(actual.is_some());
         let (actual_rack, actual_table) = actual.expect("TEST");
@@ -361,36 +382,219 @@ mod example_clearing_joker_from_rulebook {
     use super::*;
     use crate::domain::score_value::ScoreValue;
     use crate::domain::sets::*;
+    use crate::domain::table::boneyard::Boneyard;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile::{JokersWild, RegularTile};
     use crate::domain::tiles::*;
     use crate::domain::Decompose;
+    use crate::game_loop::state::PublicGameState;
+    use crate::game_loop::validation::{validate_turn, TurnViolation};
+
+    /// Helper to wrap a rack and table into the before or after of a turn, the boneyard is
+    /// irrelevant to all of these examples
+    fn turn(tiles: Vec<Tile>, sets: Vec<Set>) -> (Rack, PublicGameState) {
+        let rack = Rack::new(&tiles, Some(true)).expect("TEST");
+        let table = PublicGameState {
+            boneyard: Boneyard { bones: vec![] },
+            face_up: FaceUpTiles { sets },
+        };
+        (rack, table)
+    }
+
+    fn assert_valid_turn(before: (Rack, PublicGameState), after: (Rack, PublicGameState)) {
+        assert_eq!(
+            validate_turn(&before.0, &before.1, &after.0, &after.1),
+            Ok(())
+        );
+    }
 
     ///The player can replace the joker by each one of the tiles on his rack or by both
     #[test]
-    #[ignore]
     pub fn can_replace_joker_with_black_or_yellow_three() {
-        todo!()
+        let joker_group = Group::parse(vec![
+            RegularTile(Red, Three),
+            RegularTile(Blue, Three),
+            JokersWild,
+        ])
+        .expect("TEST");
+        let before = turn(
+            vec![
+                RegularTile(Black, Three),
+                RegularTile(Orange, Three),
+                RegularTile(Red, Ten),
+                RegularTile(Red, Eleven),
+            ],
+            vec![Set::Group(joker_group.clone())],
+        );
+
+        // Each one on its own can free the joker
+        for color in [Black, Orange] {
+            let (group, joker) = joker_group
+                .retrieve_joker(RegularTile(color, Three))
+                .expect("TEST");
+            assert_eq!(joker, JokersWild);
+            assert!(Set::Group(group).is_valid());
+        }
+
+        // Or both, the joker is then played with the red ten and eleven
+        let (group, joker) = joker_group
+            .retrieve_joker(RegularTile(Black, Three))
+            .expect("TEST");
+        let group = group
+            .insert_tile(&RegularTile(Orange, Three))
+            .expect("TEST");
+        let new_run =
+            Run::parse(&[RegularTile(Red, Ten), RegularTile(Red, Eleven), joker]).expect("TEST");
+        let after = turn(vec![], vec![Set::Group(group), Set::Run(new_run)]);
+        assert_valid_turn(before, after);
     }
 
     /// The player splits the run and clears the joker. For the record, we've never played it this way
     /// and it blows my mind
     #[test]
-    #[ignore]
     pub fn can_split_run_and_implicitly_extract_joker() {
-        todo!()
+        let joker_run = Run::parse(&[
+            RegularTile(Orange, Four),
+            RegularTile(Orange, Five),
+            RegularTile(Orange, Six),
+            JokersWild,
+            RegularTile(Orange, Eight),
+            RegularTile(Orange, Nine),
+            RegularTile(Orange, Ten),
+        ])
+        .expect("TEST");
+        let rack_tiles = vec![RegularTile(Blue, One), RegularTile(Blue, Two)];
+        let before = turn(rack_tiles.clone(), vec![Set::Run(joker_run.clone())]);
+
+        let (lesser, greater, joker) = joker_run.split_freeing_joker().expect("TEST");
+        assert_eq!(lesser, Run::of(Four, Orange, 3).expect("TEST"));
+        assert_eq!(greater, Run::of(Eight, Orange, 3).expect("TEST"));
+
+        let mut new_run_tiles = rack_tiles.clone();
+        new_run_tiles.push(joker);
+        let new_run = Run::parse(&new_run_tiles).expect("TEST");
+        let after = turn(
+            vec![],
+            vec![
+                Set::Run(lesser.clone()),
+                Set::Run(greater.clone()),
+                Set::Run(new_run),
+            ],
+        );
+        assert_valid_turn(before.clone(), after);
+
+        // The joker may not be kept on the rack for later
+        let kept = turn(
+            vec![RegularTile(Blue, One), RegularTile(Blue, Two), JokersWild],
+            vec![Set::Run(lesser), Set::Run(greater)],
+        );
+        assert_eq!(
+            validate_turn(&before.0, &before.1, &kept.0, &kept.1),
+            Err(TurnViolation::JokerNotPlayed)
+        );
     }
 
     ///The player adds the blue 5 and clears the joker.
     #[test]
-    #[ignore]
     pub fn can_simply_replace_joker_with_blue_five() {
-        todo!()
+        let joker_run = Run::parse(&[
+            RegularTile(Blue, Three),
+            RegularTile(Blue, Four),
+            JokersWild,
+            RegularTile(Blue, Six),
+        ])
+        .expect("TEST");
+        let before = turn(
+            vec![
+                RegularTile(Blue, Five),
+                RegularTile(Black, Seven),
+                RegularTile(Red, Seven),
+            ],
+            vec![Set::Run(joker_run.clone())],
+        );
+
+        let (run, joker) = joker_run
+            .retrieve_joker(RegularTile(Blue, Five))
+            .expect("TEST");
+        assert_eq!(run, Run::of(Three, Blue, 4).expect("TEST"));
+
+        let new_group = Group::parse(vec![
+            RegularTile(Black, Seven),
+            RegularTile(Red, Seven),
+            joker,
+        ])
+        .expect("TEST");
+        let after = turn(vec![], vec![Set::Run(run), Set::Group(new_group)]);
+        assert_valid_turn(before, after);
     }
 
     ///The player splits the run. He moves the black 1 to the group of ones, he moves the
     /// black 2 to the group of twos and frees the joker
     #[test]
-    #[ignore]
     pub fn can_manipulate_table_without_tile_from_rack_and_implicitly_free_joker() {
-        todo!()
+        let joker_run = Run::parse(&[RegularTile(Black, One), RegularTile(Black, Two), JokersWild])
+            .expect("TEST");
+        let ones = Group::of(One, &vec![Red, Blue, Orange]).expect("TEST");
+        let twos = Group::of(Two, &vec![Red, Blue, Orange]).expect("TEST");
+        let before = turn(
+            vec![RegularTile(Red, Twelve), RegularTile(Red, Thirteen)],
+            vec![
+                Set::Run(joker_run),
+                Set::Group(ones.clone()),
+                Set::Group(twos.clone()),
+            ],
+        );
+
+        let ones = ones.insert_tile(&RegularTile(Black, One)).expect("TEST");
+        let twos = twos.insert_tile(&RegularTile(Black, Two)).expect("TEST");
+        let new_run = Run::parse(&[
+            JokersWild,
+            RegularTile(Red, Twelve),
+            RegularTile(Red, Thirteen),
+        ])
+        .expect("TEST");
+        let after = turn(
+            vec![],
+            vec![Set::Group(ones), Set::Group(twos), Set::Run(new_run)],
+        );
+        assert_valid_turn(before, after);
+    }
+
+    /// Not from the rulebook, but how the bots make use of a retrieved joker when the rack
+    /// holds nothing else to play it with
+    #[test]
+    pub fn bot_swaps_for_joker_and_replays_it() {
+        let joker_run = Run::parse(&[
+            RegularTile(Blue, Three),
+            RegularTile(Blue, Four),
+            JokersWild,
+        ])
+        .expect("TEST");
+        let group = Group::of(Nine, &vec![Red, Orange, Black]).expect("TEST");
+        let before = turn(
+            vec![RegularTile(Blue, Five), RegularTile(Red, One)],
+            vec![Set::Run(joker_run), Set::Group(group.clone())],
+        );
+
+        let (rack, face_up) = retrieve_and_replay_joker(&before.0, &before.1.face_up)
+            .expect("Blue five frees the joker");
+        let expected = FaceUpTiles {
+            sets: vec![
+                Set::Run(Run::of(Three, Blue, 3).expect("TEST")),
+                Set::Group(group.insert_tile(&JokersWild).expect("TEST")),
+            ],
+        };
+        assert_eq!(face_up, expected);
+        assert_eq!(rack.decompose(), vec![RegularTile(Red, One)]);
+
+        let after = (
+            rack,
+            PublicGameState {
+                face_up,
+                ..before.1.clone()
+            },
+        );
+        assert_valid_turn(before, after);
     }
 }
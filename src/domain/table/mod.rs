@@ -2,3 +2,4 @@ pub mod boneyard;
 pub mod face_up;
 #[allow(unused_mut)]
 pub mod manipulation;
+pub mod solver;
@@ -0,0 +1,747 @@
+use crate::domain::player::rack::Rack;
+use crate::domain::score_value::JOKER_RACK_SCORE;
+use crate::domain::sets::group::Group;
+use crate::domain::sets::run::Run;
+use crate::domain::sets::Set;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::tiles::color::Color;
+use crate::domain::tiles::number::Number;
+use crate::domain::tiles::tile_sequence::{TileSequence, TileSequenceType};
+use crate::domain::tiles::Tile;
+use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+use crate::domain::Decompose;
+use std::collections::HashMap;
+use strum::{EnumCount, IntoEnumIterator};
+
+/// What the solver should prefer, when there is more than one way to place tiles from the rack.
+/// Whichever is not chosen is used to break ties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlacementGoal {
+    /// Get rid of as many tiles from the rack as possible
+    #[default]
+    MostTiles,
+    /// Get rid of as many points from the rack as possible, i.e. the jokers and high numbers
+    MostPoints,
+}
+
+/// Every run of a color is either not started, one or two tiles long (and must keep going), or
+/// already a valid run of three or more that can stop at any time.
+const RUN_COMPLETE: u8 = 3;
+/// With two copies of every tile, no more than two runs of the same color can overlap
+const RUNS_PER_COLOR: usize = 2;
+/// At most this many groups of the same number can be on the table at once
+const GROUPS_PER_NUMBER: u8 = 2;
+const MIN_GROUP_SIZE: u8 = 3;
+const MAX_GROUP_SIZE: u8 = 4;
+/// Weight of the preferred part of the goal, large enough that the tie breaker never outweighs it
+const GOAL_WEIGHT: i32 = 1000;
+
+/// Finds the arrangement of the face up tiles plus the rack into valid sets, that keeps every
+/// tile from the table on the table and places the most from the rack, according to the goal.
+/// Returns the new Rack and Face Up Tiles, otherwise None if no rack tile can be placed at all.
+///
+/// Unlike the greedy algorithms, this is exact: it walks the numbers from One to Thirteen, and at
+/// each number decides for every color which of its (at most two) open runs the tile extends,
+/// whether it joins a group instead, or whether a joker stands in for it. Only the lengths of the
+/// open runs matter going forward, and they are capped at three, so the number of distinct
+/// states stays small enough to solve every turn.
+pub fn solve(rack: &Rack, table: &FaceUpTiles, goal: PlacementGoal) -> Option<(Rack, FaceUpTiles)> {
+    let counts = TileCounts::of(rack, table);
+    let (score, sets) = Solver::new(&counts, goal).solve()?;
+    if score <= 0 {
+        return None;
+    }
+
+    let new_face_up = FaceUpTiles { sets };
+    let mut everything = TileSequenceType::of(rack);
+    everything.0.append(&mut table.decompose());
+    let remaining = everything.difference(&new_face_up);
+    let new_rack = Rack::new(&remaining, Some(rack.played_initial_meld)).ok()?;
+    Some((new_rack, new_face_up))
+}
+
+/// How many copies of each tile are available, split by whether they must be placed (table) or
+/// may be placed (rack)
+struct TileCounts {
+    table: [[u8; Number::COUNT]; Color::COUNT],
+    rack: [[u8; Number::COUNT]; Color::COUNT],
+    table_jokers: u8,
+    rack_jokers: u8,
+}
+
+impl TileCounts {
+    fn of(rack: &Rack, table: &FaceUpTiles) -> TileCounts {
+        let mut counts = TileCounts {
+            table: [[0; Number::COUNT]; Color::COUNT],
+            rack: [[0; Number::COUNT]; Color::COUNT],
+            table_jokers: 0,
+            rack_jokers: 0,
+        };
+        for tile in table.decompose() {
+            match tile {
+                JokersWild => counts.table_jokers += 1,
+                RegularTile(c, n) => counts.table[color_idx(c)][number_idx(n)] += 1,
+            }
+        }
+        for tile in rack.decompose() {
+            match tile {
+                JokersWild => counts.rack_jokers += 1,
+                RegularTile(c, n) => counts.rack[color_idx(c)][number_idx(n)] += 1,
+            }
+        }
+        counts
+    }
+
+    fn jokers(&self) -> u8 {
+        self.table_jokers + self.rack_jokers
+    }
+
+    /// The solver relies on there being no more than two of each tile, as in a real game
+    fn is_complete_game_subset(&self) -> bool {
+        let copies = self.table.iter().flatten().zip(self.rack.iter().flatten());
+        self.jokers() <= MAX_JOKERS && copies.into_iter().all(|(t, r)| t + r <= 2)
+    }
+}
+
+fn color_idx(color: Color) -> usize {
+    Color::iter()
+        .position(|c| c == color)
+        .expect("Every color is iterable")
+}
+
+fn number_idx(num: Number) -> usize {
+    Number::iter()
+        .position(|n| n == num)
+        .expect("Every number is iterable")
+}
+
+/// What happens to an open run of a color at the current number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Extend {
+    /// The run does not continue, only allowed if it is empty or already complete
+    Stop,
+    Real,
+    Joker,
+}
+
+/// The decision that led from one state to the next
+#[derive(Debug, Clone, Copy)]
+enum Choice {
+    /// For a single color at the current number, how each of its open runs is extended,
+    /// and how many of its tiles are put towards groups
+    Color {
+        runs: [Extend; RUNS_PER_COLOR],
+        grouped: u8,
+    },
+    /// How many jokers are added to the groups of the current number
+    Groups { jokers: u8 },
+}
+
+/// Everything about the arrangement so far that matters for what can still be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct State {
+    /// Lengths of the open runs per color, capped at complete, always sorted ascending
+    runs: [[u8; RUNS_PER_COLOR]; Color::COUNT],
+    jokers_used: u8,
+    /// Tiles of the current number which have been put towards groups
+    grouped: u8,
+    /// Whether any color put both of its copies towards groups, which requires two groups
+    grouped_pair: bool,
+}
+
+impl State {
+    fn start() -> State {
+        State {
+            runs: [[0; RUNS_PER_COLOR]; Color::COUNT],
+            jokers_used: 0,
+            grouped: 0,
+            grouped_pair: false,
+        }
+    }
+
+    fn all_runs_complete(&self) -> bool {
+        self.runs
+            .iter()
+            .flatten()
+            .all(|len| *len == 0 || *len == RUN_COMPLETE)
+    }
+
+    /// Packs the state into a dense index, so the forward pass can use flat arrays
+    fn key(&self) -> usize {
+        let mut key = 0;
+        for [a, b] in self.runs {
+            key = key * RUN_PAIRS + pair_index(a, b);
+        }
+        key = key * (MAX_JOKERS as usize + 1) + self.jokers_used as usize;
+        key = key * (MAX_GROUPED as usize + 1) + self.grouped as usize;
+        key * 2 + self.grouped_pair as usize
+    }
+}
+
+/// Number of distinct sorted pairs of run lengths, from 0 up to complete
+const RUN_PAIRS: usize = 10;
+const MAX_JOKERS: u8 = 2;
+/// Every color can put both of its copies towards groups
+const MAX_GROUPED: u8 = 2 * Color::COUNT as u8;
+/// Every possible value of State::key is below this
+const STATE_KEYS: usize =
+    RUN_PAIRS.pow(Color::COUNT as u32) * (MAX_JOKERS as usize + 1) * (MAX_GROUPED as usize + 1) * 2;
+const UNREACHED: i32 = i32::MIN;
+
+fn pair_index(shorter: u8, longer: u8) -> usize {
+    // Pairs with a shorter run of 0 come first (4 of them), then 1 (3 of them), and so on
+    let (s, l) = (shorter as usize, longer as usize);
+    let lengths = RUN_COMPLETE as usize + 1;
+    let before: usize = (0..s).map(|i| lengths - i).sum();
+    before + (l - s)
+}
+
+/// One step of the forward pass. Only the best score is kept for each reachable state, along
+/// with which state at the start of the current number it came from.
+struct Layer {
+    score: Vec<i32>,
+    origin: Vec<u32>,
+    reached: Vec<State>,
+}
+
+impl Layer {
+    fn new() -> Layer {
+        Layer {
+            score: vec![UNREACHED; STATE_KEYS],
+            origin: vec![0; STATE_KEYS],
+            reached: vec![],
+        }
+    }
+
+    fn offer(&mut self, state: State, score: i32, origin: u32) {
+        let key = state.key();
+        if self.score[key] == UNREACHED {
+            self.reached.push(state);
+        } else if self.score[key] >= score {
+            return;
+        }
+        self.score[key] = score;
+        self.origin[key] = origin;
+    }
+
+    fn clear(&mut self) {
+        for state in self.reached.drain(..) {
+            self.score[state.key()] = UNREACHED;
+        }
+    }
+}
+
+/// A reachable state, the best score found to reach it, and how it was reached
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    score: i32,
+    back: Option<(State, Choice)>,
+}
+
+struct Solver<'a> {
+    counts: &'a TileCounts,
+    goal: PlacementGoal,
+}
+
+impl<'a> Solver<'a> {
+    fn new(counts: &'a TileCounts, goal: PlacementGoal) -> Solver<'a> {
+        Solver { counts, goal }
+    }
+
+    /// Value gained by placing a single tile from the rack
+    fn gain(&self, points: u16) -> i32 {
+        let points = points as i32;
+        match self.goal {
+            PlacementGoal::MostTiles => GOAL_WEIGHT + points,
+            PlacementGoal::MostPoints => GOAL_WEIGHT * points + 1,
+        }
+    }
+
+    /// Value gained by the jokers used so far going from `before` to `after`.
+    /// The jokers from the table must be placed, so they are considered used first.
+    fn joker_gain(&self, before: u8, after: u8) -> i32 {
+        let table = self.counts.table_jokers;
+        let from_rack = after.max(table) - before.max(table);
+        from_rack as i32 * self.gain(JOKER_RACK_SCORE.as_u16())
+    }
+
+    /// Runs the dynamic program, and returns the best score and the sets that achieve it.
+    /// Keeping every choice of every step would take far too much memory, so the forward pass
+    /// only remembers where each number started from. The choices within each number are then
+    /// recovered afterwards, by solving just that number again from its known start.
+    fn solve(&self) -> Option<(i32, Vec<Set>)> {
+        if !self.counts.is_complete_game_subset() {
+            return None;
+        }
+        let mut current = Layer::new();
+        let mut next = Layer::new();
+        current.offer(State::start(), 0, 0);
+        // Per number, the states it started from, and the origin of each state it ended on
+        let mut checkpoints: Vec<(Vec<State>, HashMap<State, u32>)> = vec![];
+
+        for num in 0..Number::COUNT {
+            let starts = current.reached.clone();
+            for (idx, state) in starts.iter().enumerate() {
+                current.origin[state.key()] = idx as u32;
+            }
+            for color in 0..Color::COUNT {
+                let moves = self.color_move_table(color, num);
+                next.clear();
+                for state in &current.reached {
+                    let key = state.key();
+                    let (score, origin) = (current.score[key], current.origin[key]);
+                    let [a, b] = state.runs[color];
+                    let idx =
+                        pair_index(a, b) * (MAX_JOKERS as usize + 1) + state.jokers_used as usize;
+                    for (moved, gain) in &moves[idx] {
+                        let mut new_state = *state;
+                        new_state.runs[color] = moved.runs[color];
+                        new_state.jokers_used = moved.jokers_used;
+                        new_state.grouped += moved.grouped;
+                        new_state.grouped_pair |= moved.grouped_pair;
+                        next.offer(new_state, score + gain, origin);
+                    }
+                }
+                std::mem::swap(&mut current, &mut next);
+            }
+            next.clear();
+            for state in &current.reached {
+                let key = state.key();
+                let (score, origin) = (current.score[key], current.origin[key]);
+                self.group_moves(state, |new_state, gain, _| {
+                    next.offer(new_state, score + gain, origin)
+                });
+            }
+            std::mem::swap(&mut current, &mut next);
+            let ends = current
+                .reached
+                .iter()
+                .map(|state| (*state, current.origin[state.key()]))
+                .collect();
+            checkpoints.push((starts, ends));
+        }
+
+        let best = *current
+            .reached
+            .iter()
+            .filter(|state| {
+                state.all_runs_complete() && state.jokers_used >= self.counts.table_jokers
+            })
+            .max_by_key(|state| current.score[state.key()])?;
+
+        // Walk back through the numbers to find where each one started and ended
+        let mut ends = vec![best];
+        for (starts, origins) in checkpoints.iter().rev() {
+            let end = ends.last()?;
+            ends.push(starts[origins[end] as usize]);
+        }
+        ends.reverse();
+
+        let mut choices = vec![];
+        for (num, pair) in ends.windows(2).enumerate() {
+            choices.extend(self.replay_number(pair[0], pair[1], num)?);
+        }
+        Some((current.score[best.key()], self.build_sets(&choices)))
+    }
+
+    /// Solves a single number from a known start, and returns the choices that best reach the
+    /// known end. Starting from a single state keeps this small enough to track every choice.
+    fn replay_number(&self, start: State, end: State, num: usize) -> Option<Vec<Choice>> {
+        let mut layers: Vec<HashMap<State, Entry>> = vec![HashMap::from([(
+            start,
+            Entry {
+                score: 0,
+                back: None,
+            },
+        )])];
+        for step in 0..=Color::COUNT {
+            let mut next: HashMap<State, Entry> = HashMap::new();
+            for (state, entry) in layers.last()? {
+                let keep = |new_state, gain, choice| {
+                    keep_best(&mut next, new_state, entry.score + gain, *state, choice)
+                };
+                if step < Color::COUNT {
+                    self.color_moves(state, step, num, keep);
+                } else {
+                    self.group_moves(state, keep);
+                }
+            }
+            layers.push(next);
+        }
+
+        let mut choices = vec![];
+        let mut state = end;
+        for layer in layers.iter().rev() {
+            match layer.get(&state)?.back {
+                Some((prev, choice)) => {
+                    choices.push(choice);
+                    state = prev;
+                }
+                None => break,
+            }
+        }
+        choices.reverse();
+        Some(choices)
+    }
+
+    /// The moves of a color only depend on the lengths of its open runs and the jokers used so
+    /// far, so the forward pass works them out once per step instead of once per state.
+    /// Indexed by the pair of run lengths, then jokers used. Each move is given as the state it
+    /// leads to from an otherwise empty state, along with the score it gains.
+    fn color_move_table(&self, color: usize, num: usize) -> Vec<Vec<(State, i32)>> {
+        let mut table = vec![vec![]; RUN_PAIRS * (MAX_JOKERS as usize + 1)];
+        for shorter in 0..=RUN_COMPLETE {
+            for longer in shorter..=RUN_COMPLETE {
+                for jokers_used in 0..=MAX_JOKERS {
+                    let mut from = State::start();
+                    from.runs[color] = [shorter, longer];
+                    from.jokers_used = jokers_used;
+                    let idx = pair_index(shorter, longer) * (MAX_JOKERS as usize + 1)
+                        + jokers_used as usize;
+                    self.color_moves(&from, color, num, |to, gain, _| table[idx].push((to, gain)));
+                }
+            }
+        }
+        table
+    }
+
+    /// Every way to use the tiles of a single color at a single number, each given to `moves`
+    /// along with the score it gains
+    fn color_moves(
+        &self,
+        state: &State,
+        color: usize,
+        num: usize,
+        mut moves: impl FnMut(State, i32, Choice),
+    ) {
+        let must_place = self.counts.table[color][num];
+        let available = must_place + self.counts.rack[color][num];
+        let points = num as u16 + 1;
+        let options = [Extend::Stop, Extend::Real, Extend::Joker];
+
+        let open = state.runs[color];
+        for first in options {
+            for second in options {
+                // Two runs of the same length are interchangeable, only try one of the orders
+                if open[0] == open[1] && first > second {
+                    continue;
+                }
+                let runs = [first, second];
+                // Runs may only stop once they are complete
+                if runs
+                    .iter()
+                    .zip(open)
+                    .any(|(ext, len)| *ext == Extend::Stop && len != 0 && len != RUN_COMPLETE)
+                {
+                    continue;
+                }
+                let jokers = runs.iter().filter(|e| **e == Extend::Joker).count() as u8;
+                if state.jokers_used + jokers > self.counts.jokers() {
+                    continue;
+                }
+                let in_runs = runs.iter().filter(|e| **e == Extend::Real).count() as u8;
+                if in_runs > available {
+                    continue;
+                }
+                for grouped in 0..=(available - in_runs) {
+                    let used = in_runs + grouped;
+                    if used < must_place {
+                        continue;
+                    }
+                    let mut new_runs = [0, 0];
+                    for i in 0..RUNS_PER_COLOR {
+                        new_runs[i] = match runs[i] {
+                            Extend::Stop => 0,
+                            _ => (open[i] + 1).min(RUN_COMPLETE),
+                        };
+                    }
+                    new_runs.sort();
+                    let mut new_state = *state;
+                    new_state.runs[color] = new_runs;
+                    new_state.jokers_used += jokers;
+                    new_state.grouped += grouped;
+                    new_state.grouped_pair |= grouped == 2;
+
+                    let gain = (used - must_place) as i32 * self.gain(points)
+                        + self.joker_gain(state.jokers_used, new_state.jokers_used);
+                    moves(new_state, gain, Choice::Color { runs, grouped });
+                }
+            }
+        }
+    }
+
+    /// Every way to close out the groups of the current number, optionally padding them
+    /// with jokers
+    fn group_moves(&self, state: &State, mut moves: impl FnMut(State, i32, Choice)) {
+        for jokers in 0..=(self.counts.jokers() - state.jokers_used) {
+            if group_count(state.grouped, jokers, state.grouped_pair).is_none() {
+                continue;
+            }
+            let new_state = State {
+                jokers_used: state.jokers_used + jokers,
+                grouped: 0,
+                grouped_pair: false,
+                ..*state
+            };
+            let gain = self.joker_gain(state.jokers_used, new_state.jokers_used);
+            moves(new_state, gain, Choice::Groups { jokers });
+        }
+    }
+
+    /// Replays the choices, laying down the actual tiles into runs and groups
+    fn build_sets(&self, choices: &[Choice]) -> Vec<Set> {
+        let colors: Vec<Color> = Color::iter().collect();
+        let mut sets: Vec<Set> = vec![];
+        let mut open: Vec<[TileSequence; RUNS_PER_COLOR]> = vec![[vec![], vec![]]; Color::COUNT];
+        let mut grouped: Vec<(Color, u8)> = vec![];
+        let mut steps = choices.iter();
+
+        for num in Number::iter() {
+            for (c, color) in colors.iter().enumerate() {
+                let Some(Choice::Color { runs, grouped: g }) = steps.next() else {
+                    panic!("Solver choices out of step at {:?} {:?}", num, color);
+                };
+                for (i, ext) in runs.iter().enumerate() {
+                    match ext {
+                        Extend::Stop => {
+                            if !open[c][i].is_empty() {
+                                sets.push(Set::Run(
+                                    Run::parse(&open[c][i]).expect("Solver built invalid run"),
+                                ));
+                                open[c][i].clear();
+                            }
+                        }
+                        Extend::Real => open[c][i].push(RegularTile(*color, num)),
+                        Extend::Joker => open[c][i].push(JokersWild),
+                    }
+                }
+                // Keep the same order as the lengths in the state, so the next choice lines up
+                open[c].sort_by_key(|run| run.len().min(RUN_COMPLETE as usize));
+                grouped.push((*color, *g));
+            }
+            let Some(Choice::Groups { jokers }) = steps.next() else {
+                panic!("Solver choices out of step at groups of {:?}", num);
+            };
+            sets.extend(
+                build_groups(num, &grouped, *jokers)
+                    .into_iter()
+                    .map(Set::Group),
+            );
+            grouped.clear();
+        }
+        for runs in open {
+            for run in runs.iter().filter(|r| !r.is_empty()) {
+                sets.push(Set::Run(Run::parse(run).expect("Solver built invalid run")));
+            }
+        }
+        sets
+    }
+}
+
+fn keep_best(
+    next: &mut HashMap<State, Entry>,
+    state: State,
+    score: i32,
+    prev: State,
+    choice: Choice,
+) {
+    let candidate = Entry {
+        score,
+        back: Some((prev, choice)),
+    };
+    next.entry(state)
+        .and_modify(|e| {
+            if e.score < score {
+                *e = candidate
+            }
+        })
+        .or_insert(candidate);
+}
+
+/// How many groups the given tiles of one number can be split into, if any.
+/// Zero tiles is zero groups, otherwise every group needs three or four tiles of distinct colors,
+/// and at least one of them must be a regular tile.
+fn group_count(regular: u8, jokers: u8, has_pair: bool) -> Option<u8> {
+    if regular == 0 {
+        return if jokers == 0 { Some(0) } else { None };
+    }
+    let total = regular + jokers;
+    let fewest = if has_pair { 2 } else { 1 };
+    (fewest..=GROUPS_PER_NUMBER)
+        .find(|k| regular >= *k && total >= MIN_GROUP_SIZE * k && total <= MAX_GROUP_SIZE * k)
+}
+
+/// Deals the grouped tiles of a number out into groups, as evenly as possible
+fn build_groups(num: Number, grouped: &[(Color, u8)], jokers: u8) -> Vec<Group> {
+    let regular: u8 = grouped.iter().map(|(_, g)| g).sum();
+    let has_pair = grouped.iter().any(|(_, g)| *g == 2);
+    let count = group_count(regular, jokers, has_pair).expect("Solver built invalid groups");
+    let mut groups: Vec<TileSequence> = vec![vec![]; count as usize];
+
+    // Pairs go in every group first, so each color appears at most once in any group
+    for (color, g) in grouped.iter().filter(|(_, g)| *g == 2) {
+        for group in groups.iter_mut() {
+            group.push(RegularTile(*color, num));
+        }
+    }
+    let singles = grouped
+        .iter()
+        .filter(|(_, g)| *g == 1)
+        .map(|(color, _)| RegularTile(*color, num));
+    let padding = (0..jokers).map(|_| JokersWild);
+    for tile in singles.chain(padding) {
+        let smallest = groups
+            .iter_mut()
+            .min_by_key(|g| g.len())
+            .expect("At least one group");
+        smallest.push(tile);
+    }
+    groups
+        .into_iter()
+        .map(|tiles| Group::parse(tiles).expect("Solver built invalid group"))
+        .collect()
+}
+
+#[cfg(test)]
+mod solver_tests {
+    use super::*;
+    use crate::domain::table::boneyard::Boneyard;
+    use crate::domain::table::manipulation::shatter_and_recombobulate;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::game_loop::state::PublicGameState;
+    use crate::game_loop::validation::validate_turn;
+
+    fn assert_valid(rack: &Rack, table: &FaceUpTiles, new_rack: &Rack, new_table: &FaceUpTiles) {
+        let before = PublicGameState {
+            boneyard: Boneyard { bones: vec![] },
+            face_up: table.clone(),
+        };
+        let after = PublicGameState {
+            face_up: new_table.clone(),
+            ..before.clone()
+        };
+        assert_eq!(validate_turn(rack, &before, new_rack, &after), Ok(()));
+    }
+
+    #[test]
+    fn places_single_tile_on_run() {
+        let rack = Rack::new(&vec![RegularTile(Blue, Three)], Some(true)).unwrap();
+        let table = FaceUpTiles {
+            sets: vec![Set::Run(Run::of(Four, Blue, 3).unwrap())],
+        };
+        let (new_rack, new_table) = solve(&rack, &table, PlacementGoal::MostTiles).unwrap();
+        assert!(new_rack.is_empty());
+        assert_eq!(
+            new_table.sets,
+            vec![Set::Run(Run::of(Three, Blue, 4).unwrap())]
+        );
+    }
+
+    #[test]
+    fn nothing_placeable_is_none() {
+        let rack = Rack::new(&vec![RegularTile(Red, Nine)], Some(true)).unwrap();
+        let table = FaceUpTiles {
+            sets: vec![Set::Run(Run::of(Four, Blue, 3).unwrap())],
+        };
+        assert!(solve(&rack, &table, PlacementGoal::MostTiles).is_none());
+    }
+
+    /// Greedy takes the run of 1-5 first, and then cannot do anything with two 4s and two 5s.
+    /// The solver instead plays one run and two groups
+    #[test]
+    fn beats_the_greedy_largest_run_first() {
+        let rack = Rack::new(
+            &vec![
+                RegularTile(Red, One),
+                RegularTile(Red, Two),
+                RegularTile(Red, Three),
+                RegularTile(Red, Four),
+                RegularTile(Red, Five),
+                RegularTile(Blue, Four),
+                RegularTile(Orange, Four),
+                RegularTile(Blue, Five),
+                RegularTile(Orange, Five),
+            ],
+            Some(true),
+        )
+        .unwrap();
+        let table = FaceUpTiles::new();
+
+        let (greedy_remaining, _) = shatter_and_recombobulate(&rack, &table).unwrap();
+        assert_eq!(greedy_remaining.0.len(), 4);
+
+        let (new_rack, new_table) = solve(&rack, &table, PlacementGoal::MostTiles).unwrap();
+        assert!(new_rack.is_empty());
+        assert_valid(&rack, &table, &new_rack, &new_table);
+    }
+
+    #[test]
+    fn uses_jokers_from_rack_and_table() {
+        let rack = Rack::new(
+            &vec![
+                RegularTile(Black, Twelve),
+                RegularTile(Black, Thirteen),
+                JokersWild,
+            ],
+            Some(true),
+        )
+        .unwrap();
+        let joker_run =
+            Run::parse(&[RegularTile(Red, One), JokersWild, RegularTile(Red, Three)]).unwrap();
+        let table = FaceUpTiles {
+            sets: vec![Set::Run(joker_run)],
+        };
+        let (new_rack, new_table) = solve(&rack, &table, PlacementGoal::MostTiles).unwrap();
+        assert!(new_rack.is_empty());
+        assert_valid(&rack, &table, &new_rack, &new_table);
+    }
+
+    /// A single joker can either complete a long run of low tiles, or a short group of high ones
+    #[test]
+    fn goal_changes_what_is_placed() {
+        let rack = Rack::new(
+            &vec![
+                RegularTile(Red, One),
+                RegularTile(Red, Two),
+                RegularTile(Red, Four),
+                RegularTile(Black, Thirteen),
+                RegularTile(Blue, Thirteen),
+                JokersWild,
+            ],
+            Some(true),
+        )
+        .unwrap();
+        let table = FaceUpTiles::new();
+
+        let (by_tiles, _) = solve(&rack, &table, PlacementGoal::MostTiles).unwrap();
+        let mut by_tiles = by_tiles.decompose();
+        by_tiles.sort();
+        assert_eq!(
+            by_tiles,
+            vec![RegularTile(Blue, Thirteen), RegularTile(Black, Thirteen)]
+        );
+
+        let (by_points, _) = solve(&rack, &table, PlacementGoal::MostPoints).unwrap();
+        assert_eq!(
+            by_points.decompose(),
+            vec![
+                RegularTile(Red, One),
+                RegularTile(Red, Two),
+                RegularTile(Red, Four)
+            ]
+        );
+    }
+
+    /// The worst case, every tile in the game at once, must still be fast and place everything
+    #[test]
+    fn solves_the_entire_boneyard() {
+        let everything = Boneyard::new_game().bones;
+        let rack = Rack::new(&everything, Some(true)).unwrap();
+        let table = FaceUpTiles::new();
+        let (new_rack, new_table) = solve(&rack, &table, PlacementGoal::MostTiles).unwrap();
+        assert!(new_rack.is_empty());
+        assert_eq!(new_table.decompose().len(), everything.len());
+        assert_valid(&rack, &table, &new_rack, &new_table);
+    }
+}
@@ -1,11 +1,24 @@
 use rand::prelude::IteratorRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter};
 
 #[derive(
-    sqlx::Type, EnumIter, EnumCount, Debug, PartialOrd, Ord, PartialEq, Eq, Hash, Copy, Clone,
+    EnumIter,
+    EnumCount,
+    Serialize,
+    Deserialize,
+    Debug,
+    PartialOrd,
+    Ord,
+    PartialEq,
+    Eq,
+    Hash,
+    Copy,
+    Clone,
 )]
-#[sqlx(type_name = "color")]
+#[cfg_attr(feature = "postgres", derive(sqlx::Type), sqlx(type_name = "color"))]
 pub enum Color {
     Red,
     Blue,
@@ -15,7 +28,12 @@ pub enum Color {
 
 impl Color {
     pub fn get_rand() -> Color {
-        if let Some(col) = Color::iter().choose(&mut rand::thread_rng()) {
+        Color::get_rand_from(&mut rand::thread_rng())
+    }
+
+    /// Same as get_rand, but draws from the provided generator, so a seeded game stays reproducible
+    pub fn get_rand_from(rng: &mut impl Rng) -> Color {
+        if let Some(col) = Color::iter().choose(rng) {
             return col;
         }
         Color::Black
@@ -5,19 +5,21 @@ pub mod number;
 pub mod tile_sequence;
 
 use crate::domain::score_value::{ScoreValue, ScoringRule, JOKER_RACK_SCORE};
-use crate::domain::{Decompose, RummikubError};
+use crate::domain::{notation, Decompose, RummikubError};
 use color::Color;
 use colored::ColoredString;
 use colored::Colorize;
 use number::Number;
 use rand::seq::IteratorRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::cmp::PartialEq;
 use std::fmt::{Display, Formatter};
 use strum::IntoEnumIterator;
 use tile_sequence::TileSequence;
 use Tile::{JokersWild, RegularTile};
 
-#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[derive(Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize)]
 pub enum Tile {
     JokersWild,
     RegularTile(Color, Number),
@@ -42,6 +44,9 @@ impl Decompose for Tile {
 
 impl Display for Tile {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if f.alternate() {
+            return notation::write_tile(f, self);
+        }
         let text: ColoredString;
         match self {
             JokersWild => text = "J".bright_green(),
@@ -65,6 +70,10 @@ impl Tile {
         RegularTile(Color::get_rand(), Number::get_rand())
     }
 
+    pub fn any_regular_from(rng: &mut impl Rng) -> Self {
+        RegularTile(Color::get_rand_from(rng), Number::get_rand_from(rng))
+    }
+
     pub fn all_unique_numbered() -> Vec<Self> {
         let mut unique: Vec<Tile> = Vec::new();
         for color in Color::iter() {
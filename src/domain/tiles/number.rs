@@ -1,12 +1,26 @@
 use crate::domain::score_value::ScoreValue;
 use rand::prelude::IteratorRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 use strum_macros::{EnumCount, EnumIter, EnumString};
 
 /// Represents the ordered numeric values of the regular Rummikub tiles
 /// Not using u8's as representation in order to make illegal states unrepresentable
 #[derive(
-    Debug, PartialEq, Eq, PartialOrd, Ord, EnumString, EnumIter, EnumCount, Hash, Copy, Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    EnumString,
+    EnumIter,
+    EnumCount,
+    Serialize,
+    Deserialize,
+    Hash,
+    Copy,
+    Clone,
 )]
 pub enum Number {
     One,
@@ -26,7 +40,12 @@ pub enum Number {
 
 impl Number {
     pub fn get_rand() -> Number {
-        if let Some(bar) = Number::iter().choose(&mut rand::thread_rng()) {
+        Number::get_rand_from(&mut rand::thread_rng())
+    }
+
+    /// Same as get_rand, but draws from the provided generator, so a seeded game stays reproducible
+    pub fn get_rand_from(rng: &mut impl Rng) -> Number {
+        if let Some(bar) = Number::iter().choose(rng) {
             return bar;
         }
         Number::One
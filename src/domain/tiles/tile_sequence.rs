@@ -1,12 +1,12 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
 use std::collections::HashSet;
 
 use strum::IntoEnumIterator;
 
 use crate::domain::score_value::ScoringRule::{OnRack, OnTable};
 use crate::domain::score_value::{ScoreValue, ScoringRule};
-use crate::domain::sets::group::Group;
-use crate::domain::sets::run::Run;
+use crate::domain::sets::group::{Group, MAX_JOKERS_IN_GROUP};
+use crate::domain::sets::run::{Run, MAX_JOKERS_IN_RUN};
 use crate::domain::tiles::color::Color;
 use crate::domain::tiles::number::Number;
 use crate::domain::tiles::Tile;
@@ -88,45 +88,71 @@ impl TileSequenceType {
             .collect()
     }
 
+    /// How many jokers are in the sequence
+    pub fn jokers(&self) -> usize {
+        self.0.iter().filter(|t| t.is_joker()).count()
+    }
+
     /// Exactly the same as the Rack get_largest_run, but New!
     /// Also public.
-    /// TODO Current Implementation Ignores Jokers  -> Fix will be to increase search space by inserting them
+    /// Runs are ranked by their value on the table, where a joker is worth the tile it stands
+    /// for. Between runs of the same value, the one using fewer jokers wins.
     pub fn largest_run(&self) -> Option<Run> {
-        let mut optional_runs: Vec<Run> = vec![];
-        // don't need to remove regular tiles cuz of cool iters above
+        most_valuable_with_fewest_jokers(self.all_runs())
+    }
+
+    /// Every run that can be made from the sequence. Jokers are used to fill any gaps, or to
+    /// extend the run on either side, up to the maximum allowed in a run.
+    pub fn all_runs(&self) -> Vec<Run> {
+        let available_jokers = self.jokers().min(MAX_JOKERS_IN_RUN);
+        let mut runs: Vec<Run> = vec![];
         for color in Color::iter() {
-            let mut with_color = self.filter_color(color);
-            with_color.sort();
-            with_color.dedup();
-            let all_subsequences = list_all_subsequences(&with_color);
-            // TODO insert however many jokers into each position for each sequence here
-            optional_runs.extend(
-                all_subsequences
-                    .iter()
-                    .filter_map(|arg0: &Vec<Tile>| Run::parse(arg0)) // closure required for Slice
-                    .collect::<Vec<Run>>(), // Not sure why needed but okay
-            );
+            let with_color = self.filter_color(color);
+            for start in Number::iter() {
+                let mut candidate: TileSequence = vec![];
+                let mut jokers = 0;
+                let mut current = Some(start);
+                while let Some(num) = current {
+                    let tile = RegularTile(color, num);
+                    if with_color.contains(&tile) {
+                        candidate.push(tile);
+                    } else {
+                        jokers += 1;
+                        if jokers > available_jokers {
+                            break;
+                        }
+                        candidate.push(JokersWild);
+                    }
+                    // Fails unless long enough, and not made entirely of jokers
+                    runs.extend(Run::parse(&candidate));
+                    current = num.next();
+                }
+            }
         }
-        let valid_runs = optional_runs.into_iter().filter_map(|r| Some(r)).collect();
-        let largest_run = highest_value_collection(&valid_runs, ScoringRule::default());
-        largest_run
+        runs
     }
 
+    /// Finds the group with the highest value on the table, same ranking as for runs
     pub fn largest_group(&self) -> Option<Group> {
-        let mut optional_groups: Vec<Option<Group>> = vec![];
+        most_valuable_with_fewest_jokers(self.all_groups())
+    }
+
+    /// Every group that can be made from the sequence. Jokers complete a group of two,
+    /// or add a fourth tile, up to the maximum allowed in a group.
+    pub fn all_groups(&self) -> Vec<Group> {
+        let available_jokers = self.jokers().min(MAX_JOKERS_IN_GROUP as usize);
+        let mut groups: Vec<Group> = vec![];
         for num in Number::iter() {
             let mut all_match_num = self.filter_number(num);
+            all_match_num.sort();
             all_match_num.dedup();
-            optional_groups.push(Group::parse(all_match_num));
+            for jokers in 0..=available_jokers {
+                let mut candidate = all_match_num.clone();
+                candidate.extend(std::iter::repeat_n(JokersWild, jokers));
+                groups.extend(Group::parse(candidate));
+            }
         }
-        let largest_group = highest_value_collection(
-            &optional_groups
-                .into_iter()
-                .flatten() // flatten is more concise .filter_map(|g| g)
-                .collect::<Vec<Group>>(),
-            ScoringRule::default(),
-        );
-        largest_group
+        groups
     }
 
     /// Attempts to remove the given items from the tile sequence.
@@ -146,6 +172,19 @@ impl TileSequenceType {
         }
         Some(TileSequenceType::of(&trimmed))
     }
+
+    /// Removes as many of the given items as are present, one for one, and returns what is left.
+    /// Unlike remove, items which are not present are simply ignored
+    /// i.e. [R1, R1, B2] minus [R1, K5] -> [R1, B2]
+    pub fn difference(&self, items: &impl Decompose) -> TileSequence {
+        let mut trimmed = self.0.clone();
+        for tile in items.decompose() {
+            if let Some(pos) = trimmed.iter().position(|t| t == &tile) {
+                trimmed.remove(pos);
+            }
+        }
+        trimmed
+    }
 }
 
 /// Provides a sequence of all possible ordered sub-sequences of an array
@@ -165,6 +204,15 @@ where
     return subsequences;
 }
 
+/// Picks the set worth the most on the table, breaking ties by using the fewest jokers.
+/// Among equals, the last one is picked, same as highest_value_collection
+pub fn most_valuable_with_fewest_jokers<T: Decompose>(candidates: Vec<T>) -> Option<T> {
+    candidates.into_iter().max_by_key(|set| {
+        let jokers = set.decompose().iter().filter(|t| t.is_joker()).count();
+        (set.score(OnTable).ok(), Reverse(jokers))
+    })
+}
+
 /// Ranks a given set of Tile Sequences (or what have you) by their Scores
 /// Highest Value is first.
 pub fn highest_value_collection<T: Decompose>(collections: &Vec<T>, rule: ScoringRule) -> Option<T>
@@ -348,6 +396,22 @@ mod sequence_tests {
         assert_eq!(expected, tiles.remove(&tiles).expect("BROKEN").decompose());
     }
 
+    #[test]
+    fn test_difference_of_tile_sequences() {
+        let tiles = TileSequenceType(vec![
+            RegularTile(Red, One),
+            RegularTile(Red, One),
+            JokersWild,
+            RegularTile(Blue, Two),
+        ]);
+        let expected = vec![RegularTile(Red, One), RegularTile(Blue, Two)];
+        assert_eq!(
+            expected,
+            tiles.difference(&vec![RegularTile(Red, One), JokersWild, RegularTile(Black, Five)])
+        );
+        assert_eq!(tiles.0, tiles.difference(&vec![]));
+    }
+
     #[test]
     fn test_largest_group() {
         let tiles = TileSequenceType(vec![
@@ -367,6 +431,83 @@ mod sequence_tests {
         assert_eq!(expectation, actual.expect("BROKEN"));
     }
 
+    #[test]
+    fn test_largest_run_with_jokers() {
+        // Joker fills the gap, and the second is used to extend up to thirteen
+        let tiles = TileSequenceType(vec![
+            RegularTile(Red, Ten),
+            RegularTile(Red, Twelve),
+            JokersWild,
+            JokersWild,
+            RegularTile(Blue, Two),
+            RegularTile(Blue, Three),
+            RegularTile(Blue, Four),
+        ]);
+        // 10 + 11 + 12 + 13 with the joker at the top beats 9 + 10 + 11 + 12 with it at the bottom
+        let expected = Run::parse(&[
+            RegularTile(Red, Ten),
+            JokersWild,
+            RegularTile(Red, Twelve),
+            JokersWild,
+        ])
+        .unwrap();
+        let actual = tiles.largest_run().expect("Jokers complete a run");
+        assert_eq!(actual.score(OnTable), Ok(ScoreValue::of_u16(46)));
+        assert_eq!(actual, expected);
+
+        // Without jokers, same as before
+        let no_jokers = TileSequenceType(tiles.remove(&vec![JokersWild, JokersWild]).unwrap().0);
+        assert_eq!(no_jokers.largest_run(), Run::of(Two, Blue, 3));
+    }
+
+    #[test]
+    fn test_largest_run_prefers_fewer_jokers() {
+        let tiles = TileSequenceType(vec![
+            RegularTile(Orange, Five),
+            RegularTile(Orange, Six),
+            RegularTile(Orange, Seven),
+            RegularTile(Orange, Eight),
+            JokersWild,
+        ]);
+        // The joker is only used if it adds value, so it ends up extending the run upwards
+        let actual = tiles.largest_run().unwrap();
+        assert_eq!(actual.score(OnTable), Ok(ScoreValue::of_u16(5 + 6 + 7 + 8 + 9)));
+
+        let tiles = TileSequenceType(vec![
+            RegularTile(Orange, Eleven),
+            RegularTile(Orange, Twelve),
+            RegularTile(Orange, Thirteen),
+        ]);
+        assert_eq!(tiles.largest_run(), Run::of(Eleven, Orange, 3));
+    }
+
+    #[test]
+    fn test_largest_group_with_jokers() {
+        let tiles = TileSequenceType(vec![
+            RegularTile(Red, Thirteen),
+            RegularTile(Blue, Thirteen),
+            RegularTile(Red, One),
+            RegularTile(Blue, One),
+            RegularTile(Black, One),
+            JokersWild,
+        ]);
+        let expected = Group::parse(vec![
+            RegularTile(Red, Thirteen),
+            RegularTile(Blue, Thirteen),
+            JokersWild,
+        ])
+        .unwrap();
+        assert_eq!(tiles.largest_group(), Some(expected));
+
+        // Already complete groups are not padded with a joker for nothing but a tie
+        let tiles = TileSequenceType(vec![
+            RegularTile(Red, One),
+            RegularTile(Blue, One),
+            RegularTile(Black, One),
+        ]);
+        assert_eq!(tiles.largest_group(), Group::of(One, &vec![Red, Blue, Black]));
+    }
+
     #[test]
     fn test_scoring_tile_sequence() {
         let tiles: TileSequence = vec![
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// By the official rules, going over the turn limit means drawing this many tiles
+pub const PENALTY_TILES: usize = 3;
+
+/// Where the game loop gets the time from, so tests can decide how long a turn took
+pub trait Clock: Debug + Send + Sync {
+    /// Time passed since some fixed point, only ever compared with another reading of the same clock
+    fn now(&self) -> Duration;
+}
+
+/// The real time, counted from when the clock was made
+#[derive(Debug)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock::default())
+}
+
+/// Only moves when told to
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().expect("Clock is never poisoned") += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().expect("Clock is never poisoned")
+    }
+}
+
+/// Times every turn of a game. Only the limit is saved with the game, a loaded game uses the real clock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnTimer {
+    /// No limit at all if None
+    pub limit: Option<Duration>,
+    #[serde(skip, default = "system_clock")]
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Default for TurnTimer {
+    fn default() -> Self {
+        TurnTimer {
+            limit: None,
+            clock: system_clock(),
+        }
+    }
+}
+
+impl TurnTimer {
+    /// A turn which started at this reading of the clock, has taken longer than allowed
+    pub fn is_over(&self, started: Duration) -> bool {
+        self.limit
+            .is_some_and(|limit| self.clock.now().saturating_sub(started) > limit)
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::*;
+
+    #[test]
+    fn only_over_once_past_the_limit() {
+        let clock = Arc::new(ManualClock::default());
+        let timer = TurnTimer {
+            limit: Some(Duration::from_secs(60)),
+            clock: clock.clone(),
+        };
+        let started = timer.clock.now();
+        clock.advance(Duration::from_secs(60));
+        assert!(!timer.is_over(started));
+        clock.advance(Duration::from_millis(1));
+        assert!(timer.is_over(started));
+
+        let unlimited = TurnTimer { limit: None, clock };
+        assert!(!unlimited.is_over(Duration::ZERO));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::domain::table::boneyard::Boneyard;
+use crate::domain::tiles::tile_sequence::TileSequenceType;
+use crate::domain::tiles::Tile;
+use crate::domain::Decompose;
+use crate::game_loop::events::{replay, GameEvent};
+use crate::game_loop::meta::GameConfig;
+use crate::game_loop::state::GameState;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Something that must hold after every single turn, no matter what was played
+#[derive(Debug, Clone, PartialEq)]
+pub enum BrokenInvariant {
+    /// The set at this position on the table is neither a valid Run nor a valid Group
+    InvalidSet(usize),
+    /// A tile from the full set of 106 is nowhere to be found
+    TileMissing(Tile),
+    /// There is one more of this tile than there should be
+    TileDuplicated(Tile),
+}
+
+impl fmt::Display for BrokenInvariant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BrokenInvariant::InvalidSet(i) => write!(f, "Set #{} is not a valid run or group", i),
+            BrokenInvariant::TileMissing(t) => write!(f, "{}has gone missing", t),
+            BrokenInvariant::TileDuplicated(t) => write!(f, "{}has been duplicated", t),
+        }
+    }
+}
+
+/// Checks that the table only has valid sets, and that between the boneyard, the table and the
+/// racks there is exactly one full set of tiles
+pub fn check_invariants(state: &GameState) -> Result<(), BrokenInvariant> {
+    if let Some(i) = state.table.face_up.sets.iter().position(|s| !s.is_valid()) {
+        return Err(BrokenInvariant::InvalidSet(i));
+    }
+    let mut in_play = state.table.boneyard.bones.clone();
+    in_play.append(&mut state.table.face_up.decompose());
+    for player in &state.players {
+        in_play.append(&mut player.rack.decompose());
+    }
+    let every_tile = TileSequenceType(Boneyard::unshuffled().bones);
+    if let Some(extra) = TileSequenceType(in_play.clone())
+        .difference(&every_tile)
+        .first()
+    {
+        return Err(BrokenInvariant::TileDuplicated(*extra));
+    }
+    if let Some(missing) = every_tile.difference(&in_play).first() {
+        return Err(BrokenInvariant::TileMissing(*missing));
+    }
+    Ok(())
+}
+
+/// Steps forwards and backwards through a recorded game, one turn at a time.
+/// Turn 0 is the game as it was dealt, before anyone has played.
+pub struct ReplayDebugger {
+    /// The state after each turn, starting with the state the game started in
+    states: Vec<GameState>,
+    /// What happened during each turn, the first entry is everything before the first turn
+    events: Vec<Vec<GameEvent>>,
+    turn: usize,
+}
+
+impl ReplayDebugger {
+    /// Returns None if the events do not belong to a game starting from the given state
+    pub fn new(start: GameState, events: &[GameEvent]) -> Option<ReplayDebugger> {
+        let mut states = vec![start.clone()];
+        states.append(&mut replay(&start, events)?);
+
+        let mut by_turn = vec![vec![]];
+        let mut this_turn = vec![];
+        for event in events {
+            match event {
+                GameEvent::GameStarted { .. } | GameEvent::InitialDeal { .. } => {
+                    by_turn[0].push(event.clone())
+                }
+                _ => this_turn.push(event.clone()),
+            }
+            if event.ends_turn() {
+                by_turn.push(std::mem::take(&mut this_turn));
+            }
+        }
+        // Anything after the last turn, like the game ending, belongs to the last turn
+        by_turn
+            .last_mut()
+            .expect("Never empty")
+            .append(&mut this_turn);
+
+        Some(ReplayDebugger {
+            states,
+            events: by_turn,
+            turn: 0,
+        })
+    }
+
+    /// Deals the game again from the seed it started with, then follows its events. Only the seed
+    /// and number of players decide the deal, so this works whoever was playing.
+    /// Returns None if the log never started a game, or does not fit the game it started
+    pub fn from_log(events: &[GameEvent]) -> Option<ReplayDebugger> {
+        let (seed, num_players) = events.iter().find_map(|event| match event {
+            GameEvent::GameStarted {
+                seed, num_players, ..
+            } => Some((*seed, *num_players)),
+            _ => None,
+        })?;
+        let start = GameState::init_game(GameConfig {
+            num_players,
+            seed: Some(seed),
+            ..GameConfig::default()
+        });
+        ReplayDebugger::new(start, events)
+    }
+
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    /// The number of turns in the whole game
+    pub fn turns(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.states[self.turn]
+    }
+
+    /// Everything that happened on the current turn
+    pub fn events(&self) -> &[GameEvent] {
+        &self.events[self.turn]
+    }
+
+    /// Returns false if already at the end of the game
+    pub fn forward(&mut self) -> bool {
+        self.goto(self.turn + 1)
+    }
+
+    /// Returns false if already at the start of the game
+    pub fn back(&mut self) -> bool {
+        self.turn > 0 && self.goto(self.turn - 1)
+    }
+
+    /// Returns false, and stays put, if the game never had that many turns
+    pub fn goto(&mut self, turn: usize) -> bool {
+        if turn > self.turns() {
+            return false;
+        }
+        self.turn = turn;
+        true
+    }
+
+    /// Finds the first turn after which an invariant no longer holds
+    pub fn first_broken_invariant(&self) -> Option<(usize, BrokenInvariant)> {
+        self.states
+            .iter()
+            .enumerate()
+            .find_map(|(turn, state)| check_invariants(state).err().map(|broken| (turn, broken)))
+    }
+
+    /// Jumps to the first turn where an invariant broke, if there is one
+    pub fn goto_first_broken(&mut self) -> Option<BrokenInvariant> {
+        let (turn, broken) = self.first_broken_invariant()?;
+        self.goto(turn);
+        Some(broken)
+    }
+}
+
+impl fmt::Display for ReplayDebugger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let state = self.state();
+        writeln!(f, "--- Turn {} of {} ---", self.turn, self.turns())?;
+        for event in self.events() {
+            write!(f, "{}", event)?;
+            match event {
+                GameEvent::InitialMeldPlayed { via, .. }
+                | GameEvent::SetsPlaced { via, .. }
+                | GameEvent::TableRearranged { via, .. } => {
+                    let path: Vec<String> = via.iter().map(|step| step.to_string()).collect();
+                    writeln!(f, " (via {})", path.join(", then "))?
+                }
+                _ => writeln!(f)?,
+            }
+        }
+        writeln!(f, "Table:\n{}", state.table.face_up)?;
+        writeln!(f, "Boneyard: {} tiles", state.table.boneyard.bones.len())?;
+        for player in &state.players {
+            writeln!(f, "{}: {}", player.info, player.rack)?;
+        }
+        match check_invariants(state) {
+            Ok(()) => write!(f, "All invariants hold"),
+            Err(broken) => write!(f, "BROKEN: {}", broken),
+        }
+    }
+}
+
+#[cfg(test)]
+mod debugger_tests {
+    use super::*;
+    use crate::domain::player::rack::Rack;
+    use crate::domain::sets::run::Run;
+    use crate::domain::sets::Set;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::game_loop::events::TableDiff;
+    use crate::game_loop::events::{read_events, JsonLines};
+    use crate::game_loop::main_game_loop_with_events;
+    use crate::game_loop::strategy::all_strategies;
+
+    fn recorded(seed: u64) -> (GameState, Vec<GameEvent>) {
+        let start = GameState::init_game(GameConfig {
+            num_players: 2,
+            seed: Some(seed),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        main_game_loop_with_events(start.clone(), &mut events);
+        (start, events)
+    }
+
+    #[test]
+    fn steps_forwards_and_backwards() {
+        let (start, events) = recorded(17);
+        let mut debugger = ReplayDebugger::new(start.clone(), &events).unwrap();
+        assert_eq!(debugger.turn(), 0);
+        assert!(!debugger.back());
+        assert!(matches!(
+            debugger.events()[0],
+            GameEvent::GameStarted { .. }
+        ));
+        assert_eq!(debugger.state().players, start.players);
+
+        assert!(debugger.forward());
+        assert!(debugger.forward());
+        assert!(debugger.back());
+        assert_eq!(debugger.turn(), 1);
+        assert!(debugger.events().last().unwrap().ends_turn());
+
+        let last = debugger.turns();
+        assert!(debugger.goto(last));
+        assert!(!debugger.forward());
+        assert!(!debugger.goto(last + 1));
+        assert!(matches!(
+            debugger.events().last(),
+            Some(GameEvent::GameEnded { .. })
+        ));
+        assert!(debugger.first_broken_invariant().is_none());
+        assert!(debugger.to_string().contains("All invariants hold"));
+    }
+
+    #[test]
+    fn finds_where_a_tile_was_conjured() {
+        let (start, mut events) = recorded(17);
+        // Slip in a second turn where a tile appears on the table out of nowhere
+        let player = start.players[1].info.clone();
+        let conjured = Run::of(One, Red, 3).unwrap();
+        events.truncate(events.iter().position(|e| e.ends_turn()).unwrap() + 1);
+        events.push(GameEvent::TableRearranged {
+            player,
+            from_rack: vec![],
+            diff: TableDiff {
+                removed: vec![],
+                added: vec![Set::Run(conjured)],
+            },
+            via: vec![],
+        });
+        let mut debugger = ReplayDebugger::new(start, &events).unwrap();
+        assert!(matches!(
+            debugger.goto_first_broken(),
+            Some(BrokenInvariant::TileDuplicated(_))
+        ));
+        assert_eq!(debugger.turn(), 2);
+        assert!(debugger.to_string().contains("BROKEN"));
+    }
+
+    #[test]
+    fn replays_a_logged_game_whoever_played_it() {
+        let start = GameState::init_game(GameConfig {
+            num_players: 3,
+            seed: Some(23),
+            strategies: all_strategies(),
+            ..GameConfig::default()
+        });
+        let mut log = JsonLines(vec![]);
+        main_game_loop_with_events(start.clone(), &mut log);
+        let events = read_events(log.0.as_slice()).unwrap();
+
+        let racks = |state: &GameState| -> Vec<Rack> {
+            state.players.iter().map(|p| p.rack.clone()).collect()
+        };
+        let mut debugger = ReplayDebugger::from_log(&events).unwrap();
+        assert_eq!(racks(debugger.state()), racks(&start));
+        assert_eq!(debugger.state().table.boneyard, start.table.boneyard);
+        assert!(debugger.goto(debugger.turns()));
+        let end = replay(&start, &events).unwrap().pop().unwrap();
+        assert_eq!(racks(debugger.state()), racks(&end));
+        assert_eq!(debugger.state().table.face_up, end.table.face_up);
+        assert!(ReplayDebugger::from_log(&events[1..]).is_none());
+    }
+}
@@ -0,0 +1,521 @@
+use crate::domain::player::info::PlayerInfo;
+use crate::domain::player::rack::Rack;
+use crate::domain::sets::Set;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::tiles::tile_sequence::TileSequenceType;
+use crate::domain::tiles::Tile;
+use crate::domain::Decompose;
+use crate::game_loop::meta::GameEnd;
+use crate::game_loop::save::LoadError;
+use crate::game_loop::state::GameState;
+use crate::game_loop::strategy::MovePath;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::io::{BufRead, Write};
+
+/// Everything that can happen during a game. Together with the state the game started from,
+/// the events are enough to rebuild the game one turn at a time, see replay.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    GameStarted {
+        seed: u64,
+        num_players: u8,
+        /// The strategy of each player, in seating order
+        strategies: Vec<String>,
+    },
+    /// The tiles a player starts the game with
+    InitialDeal {
+        player: PlayerInfo,
+        tiles: Vec<Tile>,
+    },
+    /// Ends the turn
+    TileDrawn { player: PlayerInfo, tile: Tile },
+    /// Ends the turn. The player went over the turn limit, so nothing they played stays on the table,
+    /// and they drew these penalty tiles instead. Fewer if the boneyard ran out
+    TimedOut {
+        player: PlayerInfo,
+        penalty: Vec<Tile>,
+    },
+    /// Ends the turn. The first sets a player puts down, only ever from their own rack
+    InitialMeldPlayed {
+        player: PlayerInfo,
+        sets: Vec<Set>,
+        via: Vec<MovePath>,
+    },
+    /// Ends the turn. Whole sets placed from the rack, without touching anything on the table
+    SetsPlaced {
+        player: PlayerInfo,
+        sets: Vec<Set>,
+        via: Vec<MovePath>,
+    },
+    /// A joker was taken out of this set, which happens as part of a TableRearranged
+    JokerRetrieved { player: PlayerInfo, from: Set },
+    /// Ends the turn. Sets on the table were changed, using these tiles from the rack
+    TableRearranged {
+        player: PlayerInfo,
+        from_rack: Vec<Tile>,
+        diff: TableDiff,
+        /// The steps the strategy took to come up with the move
+        via: Vec<MovePath>,
+    },
+    /// Ends the turn. Nothing could be played, and there was nothing left to draw
+    Passed { player: PlayerInfo },
+    GameEnded {
+        end: GameEnd,
+        winner: PlayerInfo,
+        turns: u32,
+    },
+}
+
+impl GameEvent {
+    /// Every turn ends in exactly one of these, anything else happens along the way
+    pub fn ends_turn(&self) -> bool {
+        matches!(
+            self,
+            GameEvent::TileDrawn { .. }
+                | GameEvent::TimedOut { .. }
+                | GameEvent::InitialMeldPlayed { .. }
+                | GameEvent::SetsPlaced { .. }
+                | GameEvent::TableRearranged { .. }
+                | GameEvent::Passed { .. }
+        )
+    }
+
+    /// The player the event happened to, None for the start and end of the whole game
+    pub fn player(&self) -> Option<&PlayerInfo> {
+        match self {
+            GameEvent::GameStarted { .. } | GameEvent::GameEnded { .. } => None,
+            GameEvent::InitialDeal { player, .. }
+            | GameEvent::TileDrawn { player, .. }
+            | GameEvent::TimedOut { player, .. }
+            | GameEvent::InitialMeldPlayed { player, .. }
+            | GameEvent::SetsPlaced { player, .. }
+            | GameEvent::JokerRetrieved { player, .. }
+            | GameEvent::TableRearranged { player, .. }
+            | GameEvent::Passed { player } => Some(player),
+        }
+    }
+}
+
+/// Tiles are written in the plain notation with {:#}, see domain::notation
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEvent::GameStarted {
+                seed, num_players, ..
+            } => write!(
+                f,
+                "Game Started with {} players, seed {}",
+                num_players, seed
+            ),
+            GameEvent::InitialDeal { player, tiles } => {
+                write!(f, "{} was dealt {} tiles", player, tiles.len())
+            }
+            GameEvent::TileDrawn { player, tile } if f.alternate() => {
+                write!(f, "{} drew {:#}", player, tile)
+            }
+            GameEvent::TileDrawn { player, tile } => write!(f, "{} drew {}", player, tile),
+            GameEvent::TimedOut { player, penalty } => write!(
+                f,
+                "{} ran out of time and drew {} penalty tiles",
+                player,
+                penalty.len()
+            ),
+            GameEvent::InitialMeldPlayed { player, sets, .. } => {
+                write!(
+                    f,
+                    "{} played their initial meld of {} sets",
+                    player,
+                    sets.len()
+                )
+            }
+            GameEvent::SetsPlaced { player, sets, .. } => {
+                write!(f, "{} placed {} sets from their rack", player, sets.len())
+            }
+            GameEvent::JokerRetrieved { player, from } if f.alternate() => {
+                write!(f, "{} retrieved a joker from {:#}", player, from)
+            }
+            GameEvent::JokerRetrieved { player, from } => {
+                write!(f, "{} retrieved a joker from {}", player, from)
+            }
+            GameEvent::TableRearranged {
+                player,
+                from_rack,
+                diff,
+                ..
+            } => write!(
+                f,
+                "{} rearranged {} sets into {}, placing {} tiles",
+                player,
+                diff.removed.len(),
+                diff.added.len(),
+                from_rack.len()
+            ),
+            GameEvent::Passed { player } => write!(f, "{} passed", player),
+            GameEvent::GameEnded { winner, turns, .. } => {
+                write!(f, "Game Over after {} turns, {} wins", turns, winner)
+            }
+        }
+    }
+}
+
+/// The change to the face up tiles over a single turn. Sets which stay exactly the same
+/// are left out, so a tile added to a run shows up as the old run removed and the longer run added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableDiff {
+    pub removed: Vec<Set>,
+    pub added: Vec<Set>,
+}
+
+impl TableDiff {
+    pub fn between(before: &FaceUpTiles, after: &FaceUpTiles) -> TableDiff {
+        let mut removed = before.sets.clone();
+        let mut added = vec![];
+        for set in &after.sets {
+            match removed.iter().position(|s| s == set) {
+                Some(pos) => {
+                    removed.remove(pos);
+                }
+                None => added.push(set.clone()),
+            }
+        }
+        TableDiff { removed, added }
+    }
+
+    /// Removes the sets which were removed, and then adds the new sets onto the end.
+    /// Returns None if a set to remove is not on the table.
+    pub fn apply(&self, face_up: &FaceUpTiles) -> Option<FaceUpTiles> {
+        let mut sets = face_up.sets.clone();
+        for set in &self.removed {
+            let pos = sets.iter().position(|s| s == set)?;
+            sets.remove(pos);
+        }
+        sets.extend(self.added.iter().cloned());
+        Some(FaceUpTiles { sets })
+    }
+
+    /// Sets which had a joker in them, where that joker was moved away from the rest of its set
+    pub fn jokers_retrieved(&self) -> Vec<Set> {
+        self.removed
+            .iter()
+            .filter(|removed| removed.decompose().iter().any(|t| t.is_joker()))
+            .filter(|removed| {
+                let regular: Vec<Tile> = removed
+                    .decompose()
+                    .into_iter()
+                    .filter(|t| !t.is_joker())
+                    .collect();
+                !self.added.iter().any(|added| {
+                    let tiles = TileSequenceType::of(added);
+                    tiles.0.iter().any(|t| t.is_joker()) && tiles.remove(&regular).is_some()
+                })
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Somewhere for events to go as they happen
+pub trait EventSink {
+    fn record(&mut self, event: &GameEvent);
+}
+
+/// Keeps every event, in order
+impl EventSink for Vec<GameEvent> {
+    fn record(&mut self, event: &GameEvent) {
+        self.push(event.clone())
+    }
+}
+
+/// Throws every event away
+pub struct Discard;
+
+impl EventSink for Discard {
+    fn record(&mut self, event: &GameEvent) {}
+}
+
+/// Records every event in both sinks, the first one first
+impl<A: EventSink, B: EventSink> EventSink for (A, B) {
+    fn record(&mut self, event: &GameEvent) {
+        self.0.record(event);
+        self.1.record(event);
+    }
+}
+
+/// Writes every event as it happens, one line of JSON each, so the game can be stepped through
+/// later even if it never finished. Read it back with read_events
+pub struct JsonLines<W>(pub W);
+
+impl<W: Write> EventSink for JsonLines<W> {
+    fn record(&mut self, event: &GameEvent) {
+        let line = serde_json::to_string(event).expect("Events are always serializable");
+        if let Err(e) = writeln!(self.0, "{}", line) {
+            warn!("Could not record {}: {}", event, e);
+        }
+    }
+}
+
+/// Reads back the events written by JsonLines, skipping any blank lines
+pub fn read_events(input: impl BufRead) -> Result<Vec<GameEvent>, LoadError> {
+    let mut events = vec![];
+    for line in input.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            events.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(events)
+}
+
+/// Rebuilds the game from the state it started in, returning the state after every turn.
+/// Like during the game, the player up next is at the front of the players.
+/// Returns None if an event does not fit the game, like drawing from an empty boneyard, a turn
+/// played out of order, or a deal or seed from another game.
+pub fn replay(start: &GameState, events: &[GameEvent]) -> Option<Vec<GameState>> {
+    let mut state = start.clone();
+    let mut states = vec![];
+    for event in events {
+        match event {
+            GameEvent::GameStarted {
+                seed, num_players, ..
+            } => {
+                if *seed != start.seed || *num_players as usize != start.players.len() {
+                    return None;
+                }
+                continue;
+            }
+            GameEvent::InitialDeal { player, tiles } => {
+                let dealt = start.players.iter().find(|p| p.info == *player)?;
+                if dealt.rack.rack != *tiles {
+                    return None;
+                }
+                continue;
+            }
+            GameEvent::GameEnded { .. } => continue,
+            _ if event.player() != Some(&state.players[0].info) => return None,
+            _ => {}
+        }
+        let player = &mut state.players[0];
+        match event {
+            GameEvent::TileDrawn { tile, .. } => {
+                let (drawn, bones) = state.table.boneyard.draw_one()?;
+                if drawn != *tile {
+                    return None;
+                }
+                player.rack.add_tile_to_rack(&drawn);
+                state.table.boneyard = bones;
+            }
+            GameEvent::TimedOut { penalty, .. } => {
+                for tile in penalty {
+                    let (drawn, bones) = state.table.boneyard.draw_one()?;
+                    if drawn != *tile {
+                        return None;
+                    }
+                    player.rack.add_tile_to_rack(&drawn);
+                    state.table.boneyard = bones;
+                }
+            }
+            GameEvent::InitialMeldPlayed { sets, .. } | GameEvent::SetsPlaced { sets, .. } => {
+                let diff = TableDiff {
+                    removed: vec![],
+                    added: sets.clone(),
+                };
+                let tiles: Vec<Tile> = sets.iter().flat_map(|s| s.decompose()).collect();
+                play_from_rack(&mut player.rack, &tiles)?;
+                state.table.face_up = diff.apply(&state.table.face_up)?;
+            }
+            GameEvent::TableRearranged {
+                from_rack, diff, ..
+            } => {
+                play_from_rack(&mut player.rack, from_rack)?;
+                state.table.face_up = diff.apply(&state.table.face_up)?;
+            }
+            GameEvent::Passed { .. } => {}
+            _ => continue,
+        }
+        debug_assert!(event.ends_turn());
+        state.players.rotate_left(1);
+        states.push(state.clone());
+    }
+    Some(states)
+}
+
+/// Takes the tiles that were played off the rack, in the same way as during the game
+pub(crate) fn play_from_rack(rack: &mut Rack, tiles: &[Tile]) -> Option<()> {
+    rack.rack = TileSequenceType::of(rack).remove(&tiles.to_vec())?.0;
+    rack.played_initial_meld = true;
+    Some(())
+}
+
+#[cfg(test)]
+mod event_tests {
+    use super::*;
+    use crate::domain::sets::group::Group;
+    use crate::domain::sets::run::Run;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+    use crate::game_loop::main_game_loop_with_events;
+    use crate::game_loop::meta::GameConfig;
+
+    #[test]
+    fn diff_of_an_extended_run() {
+        let run = Run::of(Three, Red, 3).unwrap();
+        let longer = Run::of(Three, Red, 4).unwrap();
+        let group = Group::of(Nine, &vec![Red, Blue, Black]).unwrap();
+        let before = FaceUpTiles {
+            sets: vec![Set::Group(group.clone()), Set::Run(run.clone())],
+        };
+        let after = FaceUpTiles {
+            sets: vec![Set::Run(longer.clone()), Set::Group(group)],
+        };
+        let diff = TableDiff::between(&before, &after);
+        assert_eq!(diff.removed, vec![Set::Run(run)]);
+        assert_eq!(diff.added, vec![Set::Run(longer)]);
+
+        let rebuilt = diff.apply(&before).unwrap();
+        assert!(before.valid_rearrangement(vec![RegularTile(Red, Six)], &rebuilt));
+        assert!(diff.jokers_retrieved().is_empty());
+    }
+
+    #[test]
+    fn joker_moved_to_another_set_is_retrieved() {
+        let with_joker =
+            Run::parse(&[RegularTile(Red, One), JokersWild, RegularTile(Red, Three)]).unwrap();
+        let without = Run::of(One, Red, 3).unwrap();
+        let group = Group::parse(vec![
+            RegularTile(Red, Nine),
+            RegularTile(Blue, Nine),
+            JokersWild,
+        ])
+        .unwrap();
+        let diff = TableDiff {
+            removed: vec![Set::Run(with_joker.clone())],
+            added: vec![Set::Run(without), Set::Group(group)],
+        };
+        assert_eq!(diff.jokers_retrieved(), vec![Set::Run(with_joker.clone())]);
+
+        // Moving the joker along with its set is not retrieving it
+        let extended = Run::parse(&[
+            RegularTile(Red, One),
+            JokersWild,
+            RegularTile(Red, Three),
+            RegularTile(Red, Four),
+        ])
+        .unwrap();
+        let diff = TableDiff {
+            removed: vec![Set::Run(with_joker)],
+            added: vec![Set::Run(extended)],
+        };
+        assert!(diff.jokers_retrieved().is_empty());
+    }
+
+    #[test]
+    fn replay_rebuilds_every_turn() {
+        let start = GameState::init_game(GameConfig {
+            num_players: 3,
+            seed: Some(11),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        let outcome = main_game_loop_with_events(start.clone(), &mut events);
+
+        assert!(matches!(
+            events[0],
+            GameEvent::GameStarted {
+                seed: 11,
+                num_players: 3,
+                ..
+            }
+        ));
+        let deals = events
+            .iter()
+            .filter(|e| matches!(e, GameEvent::InitialDeal { .. }))
+            .count();
+        assert_eq!(deals, 3);
+        assert!(matches!(
+            events.last(),
+            Some(GameEvent::GameEnded { turns, .. }) if *turns == outcome.turns
+        ));
+
+        let states = replay(&start, &events).unwrap();
+        assert_eq!(states.len() as u32, outcome.turns);
+        for state in &states {
+            let tiles = state.table.boneyard.bones.len()
+                + state.table.face_up.decompose().len()
+                + state
+                    .players
+                    .iter()
+                    .map(|p| p.rack.rack.len())
+                    .sum::<usize>();
+            assert_eq!(tiles, 106);
+        }
+
+        // The last state holds the exact racks every player finished with
+        let last = states.last().unwrap();
+        for standing in &outcome.standings {
+            let replayed = last
+                .players
+                .iter()
+                .find(|p| p.info == standing.player.info)
+                .unwrap();
+            assert_eq!(replayed.rack, standing.player.rack);
+        }
+    }
+
+    #[test]
+    fn replay_rejects_events_that_do_not_fit() {
+        let start = GameState::init_game(GameConfig {
+            num_players: 2,
+            seed: Some(3),
+            ..GameConfig::default()
+        });
+        let player = start.players[0].info.clone();
+        let (top, _) = start.table.boneyard.draw_one().unwrap();
+        let not_on_top = *start
+            .table
+            .boneyard
+            .bones
+            .iter()
+            .find(|tile| **tile != top)
+            .unwrap();
+        let wrong_tile = GameEvent::TileDrawn {
+            player: player.clone(),
+            tile: not_on_top,
+        };
+        assert!(replay(&start, &[wrong_tile]).is_none());
+        let right_tile = GameEvent::TileDrawn {
+            player: player.clone(),
+            tile: top,
+        };
+        assert_eq!(replay(&start, &[right_tile]).unwrap().len(), 1);
+
+        // The right tile, but not their turn
+        let out_of_turn = GameEvent::TileDrawn {
+            player: start.players[1].info.clone(),
+            tile: top,
+        };
+        assert!(replay(&start, &[out_of_turn]).is_none());
+
+        // Started or dealt some other game
+        let other_seed = GameEvent::GameStarted {
+            seed: 4,
+            num_players: 2,
+            strategies: vec![],
+        };
+        assert!(replay(&start, &[other_seed]).is_none());
+        let other_count = GameEvent::GameStarted {
+            seed: start.seed,
+            num_players: 3,
+            strategies: vec![],
+        };
+        assert!(replay(&start, &[other_count]).is_none());
+        let other_deal = GameEvent::InitialDeal {
+            player,
+            tiles: start.players[1].rack.rack.clone(),
+        };
+        assert!(replay(&start, &[other_deal]).is_none());
+    }
+}
@@ -0,0 +1,374 @@
+use crate::domain::notation;
+use crate::domain::player::rack::Rack;
+use crate::domain::sets::run::Slot::{Left, Right};
+use crate::domain::sets::run::{Run, Slot};
+use crate::domain::sets::Set;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::tiles::tile_sequence::TileSequenceType;
+use crate::domain::tiles::Tile;
+use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+use crate::domain::Decompose;
+use crate::game_loop::observation::Observation;
+use crate::game_loop::validation::{validate_placement, TurnViolation};
+use std::fmt;
+use std::fmt::Formatter;
+
+/// A single move a person makes during their turn. A whole turn is any number of these,
+/// and the table only has to be valid once the turn is over.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HandMove {
+    /// Lays down a new set, made of tiles from the rack
+    Place(Set),
+    /// Adds a tile from the rack to the set at this position on the table. Slot is only needed
+    /// for runs, and when None the tile goes wherever it fits (jokers go on the right)
+    Add {
+        tile: Tile,
+        set: usize,
+        slot: Option<Slot>,
+    },
+    /// Splits the run at this position into two, the first of which keeps this many tiles
+    Split { set: usize, at: usize },
+    /// Takes a spare tile off the set at this position, i.e. from either end of a run, or
+    /// a fourth tile from a group. It has to be played somewhere else before the turn is over
+    Take { tile: Tile, set: usize },
+}
+
+/// Why a move can not be made. Unlike a TurnViolation, these are caught the moment the move
+/// is made, as they could never be fixed by later moves
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveError {
+    NotOnRack(Tile),
+    NoSuchSet(usize),
+    NotARun(usize),
+    DoesNotFit(Tile, usize),
+    CannotSplit(usize, usize),
+    NotASpare(Tile, usize),
+}
+
+/// Tiles are written in the plain notation with {:#}
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::NotOnRack(t) => {
+                notation::write_tile_in_text(f, t)?;
+                write!(f, "is not on your rack")
+            }
+            MoveError::NoSuchSet(i) => write!(f, "There is no set #{}", i),
+            MoveError::NotARun(i) => write!(f, "Set #{} is not a run", i),
+            MoveError::DoesNotFit(t, i) => {
+                notation::write_tile_in_text(f, t)?;
+                write!(f, "does not fit in set #{} there", i)
+            }
+            MoveError::CannotSplit(i, at) => {
+                write!(
+                    f,
+                    "Set #{} can not be split into two runs after {} tiles",
+                    i, at
+                )
+            }
+            MoveError::NotASpare(t, i) => {
+                notation::write_tile_in_text(f, t)?;
+                write!(
+                    f,
+                    "is not a spare in set #{}, only ends of runs and fourth tiles of groups are",
+                    i
+                )
+            }
+        }
+    }
+}
+
+/// The turn a person is building up one move at a time. Every move can be undone, right up
+/// until the turn is finished, and the finished turn goes through the same validation as any
+/// strategy's proposed turn.
+#[derive(Debug, Clone)]
+pub struct TurnInProgress {
+    start_rack: Rack,
+    start_face_up: FaceUpTiles,
+    rack: Rack,
+    face_up: FaceUpTiles,
+    /// The rack and table before each move made so far
+    undo: Vec<(Rack, FaceUpTiles)>,
+}
+
+impl TurnInProgress {
+    pub fn new(observation: &Observation) -> TurnInProgress {
+        TurnInProgress {
+            start_rack: observation.rack.clone(),
+            start_face_up: observation.face_up.clone(),
+            rack: observation.rack.clone(),
+            face_up: observation.face_up.clone(),
+            undo: vec![],
+        }
+    }
+
+    /// The rack as it is after the moves so far, including any tiles taken from the table
+    pub fn rack(&self) -> &Rack {
+        &self.rack
+    }
+
+    pub fn face_up(&self) -> &FaceUpTiles {
+        &self.face_up
+    }
+
+    /// True if no moves have been made yet, or they have all been undone
+    pub fn is_untouched(&self) -> bool {
+        self.undo.is_empty()
+    }
+
+    /// Makes the move, or leaves the turn as it was if the move is not possible
+    pub fn make(&mut self, hand_move: HandMove) -> Result<(), MoveError> {
+        let mut rack = self.rack.clone();
+        let mut sets = self.face_up.sets.clone();
+        match hand_move {
+            HandMove::Place(set) => {
+                rack = take_from_rack(&rack, &set)?;
+                sets.push(set);
+            }
+            HandMove::Add { tile, set, slot } => {
+                rack = take_from_rack(&rack, &tile)?;
+                let (changed, extra) = match sets.get(set).ok_or(MoveError::NoSuchSet(set))? {
+                    Set::Group(group) => (group.insert_tile(&tile).map(Set::Group), None),
+                    Set::Run(run) => {
+                        let slot = slot.or_else(|| match tile {
+                            JokersWild => Some(Right),
+                            RegularTile(..) => run.all_possible_slots()?.get(&tile).copied(),
+                        });
+                        match slot.and_then(|slot| run.insert_tile(tile, slot)) {
+                            Some((lesser, greater)) => {
+                                (Some(Set::Run(lesser)), greater.map(Set::Run))
+                            }
+                            None => (None, None),
+                        }
+                    }
+                };
+                sets[set] = changed.ok_or(MoveError::DoesNotFit(tile, set))?;
+                if let Some(greater) = extra {
+                    sets.insert(set + 1, greater);
+                }
+            }
+            HandMove::Split { set, at } => {
+                let Set::Run(run) = sets.get(set).ok_or(MoveError::NoSuchSet(set))? else {
+                    return Err(MoveError::NotARun(set));
+                };
+                let tiles = run.decompose();
+                let (lesser, greater) = match at < tiles.len() {
+                    true => tiles.split_at(at),
+                    false => return Err(MoveError::CannotSplit(set, at)),
+                };
+                match (Run::parse(lesser), Run::parse(greater)) {
+                    (Some(lesser), Some(greater)) => {
+                        sets[set] = Set::Run(lesser);
+                        sets.insert(set + 1, Set::Run(greater));
+                    }
+                    _ => return Err(MoveError::CannotSplit(set, at)),
+                }
+            }
+            HandMove::Take { tile, set } => {
+                let taken_from = sets.get(set).ok_or(MoveError::NoSuchSet(set))?;
+                sets[set] = take_spare(taken_from, tile).ok_or(MoveError::NotASpare(tile, set))?;
+                rack.add_tile_to_rack(&tile);
+            }
+        }
+        self.undo.push((self.rack.clone(), self.face_up.clone()));
+        self.rack = rack;
+        self.face_up = FaceUpTiles { sets };
+        Ok(())
+    }
+
+    /// Takes back the last move. Returns false if there was nothing left to take back
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some((rack, face_up)) => {
+                self.rack = rack;
+                self.face_up = face_up;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Checks the turn exactly as the game will, so it can be fixed before it is ended.
+    /// Gives back the rack and face up tiles to be proposed for the turn
+    pub fn finish(&self) -> Result<(Rack, FaceUpTiles), TurnViolation> {
+        validate_placement(
+            &self.start_rack,
+            &self.start_face_up,
+            &self.rack,
+            &self.face_up,
+        )?;
+        Ok((self.rack.clone(), self.face_up.clone()))
+    }
+}
+
+fn take_from_rack(rack: &Rack, tiles: &impl Decompose) -> Result<Rack, MoveError> {
+    if let Some(missing) = TileSequenceType::of(tiles).difference(rack).first() {
+        return Err(MoveError::NotOnRack(*missing));
+    }
+    Ok(rack.remove(tiles).expect("Every tile is on the rack"))
+}
+
+/// The set that is left behind once the spare is taken. Jokers are never spares,
+/// they can only be retrieved by replacing them
+fn take_spare(set: &Set, tile: Tile) -> Option<Set> {
+    match (set, tile) {
+        (Set::Group(group), RegularTile(color, num)) if num == group.get_group_num() => {
+            group.extract_spare(color).map(|(rest, _)| Set::Group(rest))
+        }
+        (Set::Run(_), RegularTile(..)) => {
+            let tiles = set.decompose();
+            let rest = match (tiles.first(), tiles.last()) {
+                (Some(first), _) if *first == tile => &tiles[1..],
+                (_, Some(last)) if *last == tile => &tiles[..tiles.len() - 1],
+                _ => return None,
+            };
+            Run::parse(rest).map(Set::Run)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod human_tests {
+    use super::*;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+
+    fn turn(rack: &str, table: &str, melded: bool) -> TurnInProgress {
+        let mut rack: Rack = rack.parse().unwrap();
+        rack.played_initial_meld = melded;
+        TurnInProgress::new(&Observation::new(&rack, &table.parse().unwrap()))
+    }
+
+    #[test]
+    fn initial_meld_by_hand() {
+        let mut turn = turn("[R10 R11 R12 B1 K7]", "", false);
+        assert_eq!(
+            turn.make(HandMove::Place("R10-R12".parse().unwrap())),
+            Ok(())
+        );
+        let (rack, face_up) = turn.finish().unwrap();
+        assert_eq!(format!("{:#}", rack), "[B1 K7]");
+        assert_eq!(format!("{:#}", face_up), "R10-R12");
+    }
+
+    #[test]
+    fn initial_meld_too_low_is_explained() {
+        let mut turn = turn("[R1 R2 R3 K7]", "", false);
+        turn.make(HandMove::Place("R1-R3".parse().unwrap()))
+            .unwrap();
+        assert!(matches!(
+            turn.finish(),
+            Err(TurnViolation::InitialMeldTooLow(_))
+        ));
+        assert_eq!(
+            turn.make(HandMove::Place("R1-R3".parse().unwrap())),
+            Err(MoveError::NotOnRack(RegularTile(Red, One)))
+        );
+    }
+
+    /// Blue 4,5,6 are on the table, blue 3 goes on the left and a blue 8 joins the group of 8s
+    #[test]
+    fn add_to_runs_and_groups() {
+        let mut turn = turn("[B3 B8 J]", "B4-B6 | R8 O8 K8", true);
+        let b3 = RegularTile(Blue, Three);
+        turn.make(HandMove::Add {
+            tile: b3,
+            set: 0,
+            slot: None,
+        })
+        .unwrap();
+        let b8 = RegularTile(Blue, Eight);
+        turn.make(HandMove::Add {
+            tile: b8,
+            set: 1,
+            slot: None,
+        })
+        .unwrap();
+        assert_eq!(
+            turn.make(HandMove::Add {
+                tile: JokersWild,
+                set: 1,
+                slot: None
+            }),
+            Err(MoveError::DoesNotFit(JokersWild, 1))
+        );
+        turn.make(HandMove::Add {
+            tile: JokersWild,
+            set: 0,
+            slot: Some(Left),
+        })
+        .unwrap();
+        assert_eq!(
+            format!("{:#}", turn.face_up()),
+            "J B3 B4 B5 B6\nR8 B8 O8 K8"
+        );
+        assert!(turn.finish().is_ok());
+    }
+
+    /// The rulebook example of splitting a run: red 6 wedged into red 4 to 8
+    #[test]
+    fn wedge_and_split_runs() {
+        let mut turn = turn("[R6 R1]", "R4-R8 | R1-R6", true);
+        let r6 = RegularTile(Red, Six);
+        turn.make(HandMove::Add {
+            tile: r6,
+            set: 0,
+            slot: None,
+        })
+        .unwrap();
+        assert_eq!(format!("{:#}", turn.face_up()), "R4-R6\nR6-R8\nR1-R6");
+        turn.make(HandMove::Split { set: 2, at: 3 }).unwrap();
+        assert_eq!(
+            turn.make(HandMove::Split { set: 2, at: 2 }),
+            Err(MoveError::CannotSplit(2, 2))
+        );
+        assert_eq!(
+            turn.make(HandMove::Split { set: 4, at: 3 }),
+            Err(MoveError::NoSuchSet(4))
+        );
+        assert_eq!(
+            format!("{:#}", turn.face_up()),
+            "R4-R6\nR6-R8\nR1-R3\nR4-R6"
+        );
+    }
+
+    #[test]
+    fn taken_spares_must_be_played() {
+        let mut turn = turn("[K1 K2]", "R4-R7 | R3 B3 O3 K3", true);
+        let k3 = RegularTile(Black, Three);
+        let r4 = RegularTile(Red, Four);
+        assert_eq!(
+            turn.make(HandMove::Take {
+                tile: RegularTile(Red, Five),
+                set: 0
+            }),
+            Err(MoveError::NotASpare(RegularTile(Red, Five), 0))
+        );
+        turn.make(HandMove::Take { tile: k3, set: 1 }).unwrap();
+        turn.make(HandMove::Take { tile: r4, set: 0 }).unwrap();
+        assert_eq!(turn.finish(), Err(TurnViolation::TileTakenFromTable(r4)));
+        assert!(turn.undo());
+        assert_eq!(turn.finish(), Err(TurnViolation::TileTakenFromTable(k3)));
+
+        turn.make(HandMove::Place("K1-K3".parse().unwrap()))
+            .unwrap();
+        let (rack, face_up) = turn.finish().unwrap();
+        assert!(rack.is_empty());
+        assert_eq!(format!("{:#}", face_up), "R4-R7\nR3 B3 O3\nK1-K3");
+    }
+
+    #[test]
+    fn undo_back_to_the_start() {
+        let mut turn = turn("[R1 B1 O1]", "", true);
+        assert!(turn.is_untouched());
+        turn.make(HandMove::Place("R1 B1 O1".parse().unwrap()))
+            .unwrap();
+        assert!(!turn.is_untouched());
+        assert!(turn.undo());
+        assert!(!turn.undo());
+        assert!(turn.is_untouched());
+        assert_eq!(format!("{:#}", turn.rack()), "[R1 B1 O1]");
+        assert_eq!(turn.finish(), Err(TurnViolation::NoTilesPlaced));
+    }
+}
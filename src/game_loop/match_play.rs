@@ -0,0 +1,272 @@
+use crate::domain::player::info::PlayerInfo;
+use crate::game_loop::main_game_loop;
+use crate::game_loop::meta::{GameConfig, GameRng};
+use crate::game_loop::state::GameState;
+use log::info;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// When a match is over
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchLength {
+    /// Play exactly this many rounds
+    Rounds(u32),
+    /// Keep playing rounds until someone's total score reaches this target
+    TargetScore(i32),
+}
+
+/// Information used to control a match, a series of games between the same players
+#[derive(Debug, Clone)]
+pub struct MatchConfig {
+    /// Used for every round. The seed, if given, seeds the whole match, and each round
+    /// gets its own seed derived from it
+    pub game: GameConfig,
+    pub length: MatchLength,
+}
+
+impl Default for MatchConfig {
+    fn default() -> Self {
+        MatchConfig {
+            game: GameConfig::default(),
+            length: MatchLength::Rounds(4),
+        }
+    }
+}
+
+/// The result of a single round (a single game) of the match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundResult {
+    /// Counting from 1
+    pub round: u32,
+    /// Replaying a game with this seed reproduces the round
+    pub seed: u64,
+    pub starting_player: PlayerInfo,
+    /// More than one if the round ended in a tie
+    pub winners: Vec<PlayerInfo>,
+    /// How the score of every player changed this round, ordered by player
+    pub deltas: Vec<(PlayerInfo, i32)>,
+}
+
+/// Where a player stands after one or more rounds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Standing {
+    pub player: PlayerInfo,
+    pub rounds_won: u32,
+    pub score: i32,
+}
+
+/// Everything that happened over a whole match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchSummary {
+    pub seed: u64,
+    pub rounds: Vec<RoundResult>,
+    /// Ordered from first place to last
+    pub standings: Vec<Standing>,
+}
+
+impl MatchSummary {
+    /// The winner of the match is the player with the highest total score.
+    /// If that is a tie, the one who won the most rounds wins. None if no rounds were played
+    pub fn winner(&self) -> Option<&Standing> {
+        self.standings.first()
+    }
+}
+
+impl fmt::Display for MatchSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Match Seed: {}", self.seed)?;
+        for round in &self.rounds {
+            let winners: Vec<String> = round.winners.iter().map(|w| w.to_string()).collect();
+            write!(
+                f,
+                "Round {} (seed {}, {} started): {} won |",
+                round.round,
+                round.seed,
+                round.starting_player,
+                winners.join(" and ")
+            )?;
+            for (player, delta) in &round.deltas {
+                write!(f, " {}: {:+}", player, delta)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "Final Standings:")?;
+        for (place, standing) in self.standings.iter().enumerate() {
+            writeln!(
+                f,
+                "{}. {} with {} rounds won and {} pts",
+                place + 1,
+                standing.player,
+                standing.rounds_won,
+                standing.score
+            )?;
+        }
+        match self.winner() {
+            Some(winner) => write!(f, "Match Winner is: {}!", winner.player),
+            None => write!(f, "No rounds were played, so nobody won"),
+        }
+    }
+}
+
+/// Plays a whole match. Every round is a fresh game between the same players, with the
+/// first player moving one seat to the left each round, and the scores carried over.
+pub fn play_match(conf: MatchConfig) -> MatchSummary {
+    let seed = conf.game.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut match_rng = GameRng::seed_from_u64(seed);
+    let mut totals: BTreeMap<PlayerInfo, Standing> = BTreeMap::new();
+    let mut rounds = vec![];
+
+    while !match_over(conf.length, rounds.len() as u32, &totals) {
+        let round = rounds.len() as u32 + 1;
+        let round_seed = match_rng.gen::<u64>();
+        let mut game = GameState::init_game(GameConfig {
+            seed: Some(round_seed),
+            ..conf.game.clone()
+        });
+        let seats = game.players.len();
+        game.players.rotate_left((round as usize - 1) % seats);
+        let starting_player = game.players[0].info.clone();
+        info!(
+            "\n=== Round {} of the Match! {} Starts ===\n",
+            round, starting_player
+        );
+
+        let outcome = main_game_loop(game);
+        let mut deltas: Vec<(PlayerInfo, i32)> = outcome
+            .standings
+            .iter()
+            .map(|s| (s.player.info.clone(), s.delta))
+            .collect();
+        deltas.sort();
+        for (player, delta) in &deltas {
+            let standing = totals.entry(player.clone()).or_insert(Standing {
+                player: player.clone(),
+                rounds_won: 0,
+                score: 0,
+            });
+            standing.score += delta;
+        }
+        let winners: Vec<PlayerInfo> = outcome
+            .winners()
+            .iter()
+            .map(|w| w.player.info.clone())
+            .collect();
+        for winner in &winners {
+            totals
+                .get_mut(winner)
+                .expect("Winner was scored")
+                .rounds_won += 1;
+        }
+        rounds.push(RoundResult {
+            round,
+            seed: round_seed,
+            starting_player,
+            winners,
+            deltas,
+        });
+    }
+
+    let mut standings: Vec<Standing> = totals.into_values().collect();
+    standings.sort_by_key(|s| (Reverse(s.score), Reverse(s.rounds_won)));
+    MatchSummary {
+        seed,
+        rounds,
+        standings,
+    }
+}
+
+fn match_over(length: MatchLength, played: u32, totals: &BTreeMap<PlayerInfo, Standing>) -> bool {
+    match length {
+        MatchLength::Rounds(rounds) => played >= rounds,
+        MatchLength::TargetScore(target) => totals.values().any(|s| s.score >= target),
+    }
+}
+
+#[cfg(test)]
+mod match_play_tests {
+    use super::*;
+
+    fn conf(seed: u64, length: MatchLength) -> MatchConfig {
+        MatchConfig {
+            game: GameConfig {
+                num_players: 3,
+                seed: Some(seed),
+                ..GameConfig::default()
+            },
+            length,
+        }
+    }
+
+    #[test]
+    fn plays_the_given_number_of_rounds() {
+        let summary = play_match(conf(8, MatchLength::Rounds(3)));
+        assert_eq!(summary.rounds.len(), 3);
+        assert_eq!(summary.standings.len(), 3);
+
+        // Starting player moves round by round, and every round is zero sum
+        let starters: Vec<String> = summary
+            .rounds
+            .iter()
+            .map(|r| r.starting_player.to_string())
+            .collect();
+        assert_eq!(starters, vec!["Player 1", "Player 2", "Player 3"]);
+        for round in &summary.rounds {
+            assert_eq!(round.deltas.iter().map(|(_, d)| d).sum::<i32>(), 0);
+        }
+
+        // Standings carry over, and are ordered by total score
+        let total_wins: u32 = summary.standings.iter().map(|s| s.rounds_won).sum();
+        let round_wins: usize = summary.rounds.iter().map(|r| r.winners.len()).sum();
+        assert_eq!(total_wins as usize, round_wins);
+        assert!(summary
+            .standings
+            .windows(2)
+            .all(|w| w[0].score >= w[1].score));
+        assert_eq!(summary.winner(), summary.standings.first());
+        for standing in &summary.standings {
+            let sum: i32 = summary
+                .rounds
+                .iter()
+                .flat_map(|r| &r.deltas)
+                .filter(|(p, _)| *p == standing.player)
+                .map(|(_, d)| d)
+                .sum();
+            assert_eq!(sum, standing.score);
+        }
+    }
+
+    #[test]
+    fn same_seed_same_match() {
+        let first = play_match(conf(21, MatchLength::Rounds(2)));
+        let second = play_match(conf(21, MatchLength::Rounds(2)));
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn no_rounds_no_winner() {
+        let summary = play_match(conf(2, MatchLength::Rounds(0)));
+        assert!(summary.rounds.is_empty());
+        assert_eq!(summary.winner(), None);
+        assert!(summary.to_string().contains("nobody won"));
+    }
+
+    #[test]
+    fn plays_until_target_score() {
+        let summary = play_match(conf(3, MatchLength::TargetScore(100)));
+        // Whoever reached the target is in first place, however many rounds they won
+        assert!(summary.winner().unwrap().score >= 100);
+        let before_last = &summary.rounds[..summary.rounds.len() - 1];
+        for standing in &summary.standings {
+            let sum: i32 = before_last
+                .iter()
+                .flat_map(|r| &r.deltas)
+                .filter(|(p, _)| *p == standing.player)
+                .map(|(_, d)| d)
+                .sum();
+            assert!(sum < 100);
+        }
+    }
+}
@@ -1,37 +1,294 @@
+use crate::domain::player::info::PlayerInfo;
 use crate::domain::player::Player;
 use crate::domain::score_value::ScoringRule::OnRack;
 use crate::domain::score_value::{ScoreValue, MAX_SCORE_VALUE};
 use crate::domain::Decompose;
+use crate::game_loop::clock::{system_clock, Clock};
+use crate::game_loop::strategy::Strategy;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::Formatter;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The one source of randomness for a game. ChaCha is used instead of StdRng because its output
+/// is guaranteed to be stable across platforms and versions, so a seed always replays the same game
+pub type GameRng = ChaCha8Rng;
 
 /// The final outcome for a given game
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GameOutcome {
     pub winner: Player,
     pub loser: Player,
+    /// Every player, from first place to last. Players on the same rank are tied
+    pub standings: Vec<FinalStanding>,
+    /// How many turns were taken in total, by all players
+    pub turns: u32,
+    pub end: GameEnd,
+}
+
+/// How a game came to an end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEnd {
+    /// The winner played every last tile from their rack
+    RackEmptied,
+    /// The boneyard ran out and a full round went by without anyone playing, so the lowest rack wins
+    Blocked,
+}
+
+impl fmt::Display for GameEnd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameEnd::RackEmptied => write!(f, "the winner emptied their rack"),
+            GameEnd::Blocked => write!(f, "the boneyard ran out and nobody could play"),
+        }
+    }
+}
+
+/// Where a single player finished in a game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinalStanding {
+    /// Counting from 1, players with racks of the same value share a rank
+    pub rank: usize,
+    pub player: Player,
+    /// The value of the tiles left on the rack, where a Joker is worth 30
+    pub rack_score: ScoreValue,
+    /// Points won or lost this game, by the official rules. Adds up to zero over all players
+    pub delta: i32,
+}
+
+impl GameOutcome {
+    /// Ranks and scores every player by the official rules. Every loser subtracts the value of
+    /// the tiles left on their rack, and the winner adds up what all the losers lost.
+    /// If nobody went out (the boneyard ran dry) each loser only loses the difference between their
+    /// rack and the lowest rack, and if the lowest rack is tied the winners split the total evenly.
+    /// Players should be given in turn order, which decides the winner of a tie.
+    pub fn new(players: Vec<Player>, turns: u32, end: GameEnd) -> GameOutcome {
+        let rack_score = |p: &Player| {
+            p.rack
+                .score(OnRack)
+                .unwrap_or(ScoreValue::of_u16(MAX_SCORE_VALUE))
+        };
+        let mut standings: Vec<FinalStanding> = players
+            .iter()
+            .map(|p| FinalStanding {
+                rank: 1 + players
+                    .iter()
+                    .filter(|other| rack_score(other) < rack_score(p))
+                    .count(),
+                player: p.clone(),
+                rack_score: rack_score(p),
+                delta: 0,
+            })
+            .collect();
+        standings.sort_by_key(|s| s.rank); // Stable, so ties stay in turn order
+
+        let lowest = standings[0].rack_score.as_u16() as i32;
+        let mut winnings = 0;
+        for standing in standings.iter_mut().filter(|s| s.rank > 1) {
+            standing.delta = lowest - standing.rack_score.as_u16() as i32;
+            winnings -= standing.delta;
+        }
+        let winners = standings.iter().filter(|s| s.rank == 1).count() as i32;
+        for (i, standing) in standings.iter_mut().filter(|s| s.rank == 1).enumerate() {
+            // Any leftover from an uneven split goes to the first winner, so the game stays zero sum
+            standing.delta = winnings / winners + if i == 0 { winnings % winners } else { 0 };
+        }
+
+        GameOutcome {
+            winner: standings[0].player.clone(),
+            loser: standings.last().expect("A game has players").player.clone(),
+            standings,
+            turns,
+            end,
+        }
+    }
+
+    /// Everyone who finished in first place
+    pub fn winners(&self) -> Vec<&FinalStanding> {
+        self.standings.iter().filter(|s| s.rank == 1).collect()
+    }
+
+    pub fn is_tie(&self) -> bool {
+        self.winners().len() > 1
+    }
 }
 
 impl fmt::Display for GameOutcome {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(
+        writeln!(f, "Game Over after {} turns, {}!", self.turns, self.end)?;
+        writeln!(
             f,
-            "Winner is: {}! With Score: {}.\n Highest Score at End Game(THE LOSER) was {} with {} pts",
-            self.winner.info,
-            self.winner
-                .rack
-                .score(OnRack)
-                .unwrap_or(ScoreValue::of_u16(MAX_SCORE_VALUE)),
-            self.loser.info,
-            self.loser
-                .rack
-                .score(OnRack)
-                .unwrap_or(ScoreValue::of_u16(MAX_SCORE_VALUE)) // of_u16 is called eagerly
-        )
+            "{:<6}{:<14}{:>6}{:>8}",
+            "Rank", "Player", "Rack", "Points"
+        )?;
+        for standing in &self.standings {
+            writeln!(
+                f,
+                "{:<6}{:<14}{:>6}{:>+8}",
+                standing.rank,
+                standing.player.info.to_string(),
+                standing.rack_score.to_string(),
+                standing.delta
+            )?;
+        }
+        if self.is_tie() {
+            let winners: Vec<String> = self
+                .winners()
+                .iter()
+                .map(|s| s.player.info.to_string())
+                .collect();
+            write!(f, "It's a Tie between {}!", winners.join(" and "))
+        } else {
+            write!(f, "Winner is: {}!", self.winner.info)
+        }
     }
 }
 
 /// Information used to control the type of game played (i.e. number of players)
+#[derive(Debug, Clone)]
 pub struct GameConfig {
-    // TODO add some kind of random seed as optional
     pub num_players: u8,
+    /// Seeds the shuffle, and every other random choice made during the game.
+    /// If None, a seed is chosen at random (and recorded in the GameState)
+    pub seed: Option<u64>,
+    /// Strategies are handed out to players in seating order, repeating if there are fewer
+    /// strategies than players. If empty, every player uses the default strategy
+    pub strategies: Vec<Arc<dyn Strategy>>,
+    /// How long each player has for a turn. Going over it puts the table back the way it was,
+    /// and the player draws the penalty tiles instead. If None, players can take as long as they like
+    pub turn_limit: Option<Duration>,
+    /// Times every turn against the turn limit
+    pub clock: Arc<dyn Clock>,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            num_players: 4,
+            seed: None,
+            strategies: vec![],
+            turn_limit: None,
+            clock: system_clock(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod outcome_tests {
+    use super::*;
+    use crate::domain::player::info::PlayerInfo;
+    use crate::domain::player::rack::Rack;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile;
+    use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+    use crate::game_loop::strategy::default_strategy;
+
+    fn player(name: &str, rack: Vec<Tile>) -> Player {
+        Player {
+            info: PlayerInfo::of(&name.to_string()),
+            rack: Rack {
+                rack,
+                played_initial_meld: true,
+            },
+            strategy: default_strategy(),
+        }
+    }
+
+    fn ranked(outcome: &GameOutcome) -> Vec<(String, usize, u16, i32)> {
+        outcome
+            .standings
+            .iter()
+            .map(|s| {
+                (
+                    s.player.info.to_string(),
+                    s.rank,
+                    s.rack_score.as_u16(),
+                    s.delta,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn winner_takes_what_losers_lose() {
+        let outcome = GameOutcome::new(
+            vec![
+                player("1", vec![]),
+                player("2", vec![RegularTile(Blue, Thirteen)]),
+                player("3", vec![RegularTile(Red, Five), JokersWild]),
+            ],
+            40,
+            GameEnd::RackEmptied,
+        );
+        assert_eq!(
+            ranked(&outcome),
+            vec![
+                ("Player 1".to_string(), 1, 0, 48),
+                ("Player 2".to_string(), 2, 13, -13),
+                ("Player 3".to_string(), 3, 35, -35),
+            ]
+        );
+        assert_eq!(outcome.winner.info, PlayerInfo::of(&"1".to_string()));
+        assert_eq!(outcome.loser.info, PlayerInfo::of(&"3".to_string()));
+        assert!(!outcome.is_tie());
+    }
+
+    /// Names come before racks when comparing players, which must not decide the loser
+    #[test]
+    fn loser_is_the_highest_rack_not_the_last_name() {
+        let outcome = GameOutcome::new(
+            vec![
+                player("z", vec![]),
+                player("a", vec![RegularTile(Red, Nine)]),
+                player("b", vec![RegularTile(Red, One)]),
+            ],
+            10,
+            GameEnd::RackEmptied,
+        );
+        assert_eq!(outcome.loser.info, PlayerInfo::of(&"a".to_string()));
+    }
+
+    #[test]
+    fn tied_winners_split_the_total() {
+        let outcome = GameOutcome::new(
+            vec![
+                player("1", vec![RegularTile(Red, Two)]),
+                player("2", vec![RegularTile(Red, Nine)]),
+                player("3", vec![RegularTile(Blue, Two)]),
+            ],
+            90,
+            GameEnd::Blocked,
+        );
+        assert!(outcome.is_tie());
+        assert_eq!(
+            ranked(&outcome),
+            vec![
+                ("Player 1".to_string(), 1, 2, 4),
+                ("Player 3".to_string(), 1, 2, 3),
+                ("Player 2".to_string(), 3, 9, -7),
+            ]
+        );
+    }
+
+    #[test]
+    fn results_table() {
+        let outcome = GameOutcome::new(
+            vec![
+                player("1", vec![]),
+                player("2", vec![RegularTile(Blue, Thirteen)]),
+            ],
+            12,
+            GameEnd::RackEmptied,
+        );
+        let expected = "\
+Game Over after 12 turns, the winner emptied their rack!
+Rank  Player          Rack  Points
+1     Player 1           0     +13
+2     Player 2          13     -13
+Winner is: Player 1!";
+        assert_eq!(outcome.to_string(), expected);
+    }
 }
@@ -1,5 +1,15 @@
+pub mod clock;
+pub mod debugger;
+pub mod events;
+pub mod human;
+pub mod match_play;
 pub mod meta;
+pub mod observation;
+pub mod save;
+pub mod simulation;
 pub mod state;
+pub mod strategy;
+pub mod validation;
 
 use crate::domain::table::boneyard::Boneyard;
 
@@ -8,97 +18,236 @@ use crate::domain::player::rack::Rack;
 use crate::domain::player::Player;
 use crate::domain::score_value::ScoreValue;
 use crate::domain::table::face_up::FaceUpTiles;
-use crate::domain::table::manipulation::rearrange;
-use meta::GameOutcome;
+use crate::domain::tiles::tile_sequence::TileSequenceType;
+use crate::domain::Decompose;
+use clock::{TurnTimer, PENALTY_TILES};
+use events::{play_from_rack, Discard, EventSink, GameEvent, TableDiff};
+use log::{debug, info};
+use meta::{GameEnd, GameOutcome};
+use observation::{Observation, Seen};
 use state::{GameState, PublicGameState};
+use strategy::Strategy;
+use validation::validate_turn;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
 use std::fmt::Formatter;
+use std::sync::Arc;
 
 /// Modifies Potentially the Entire Table, and returns a new game state
 /// Cannot Modify Other Player Racks, but can modify itself
-pub fn take_turn(prev_rack: &Rack, prev_table: &PublicGameState) -> (Rack, PublicGameState) {
-    // TODO theres an infinite loop somewhere in here.
+/// The player's strategy decides what to play from what they can observe, and every proposal is
+/// validated against the rules and the whole table
+/// If it proposes nothing, or the proposal is rejected, the player must draw
+/// Once the boneyard is empty there is nothing to draw, so the player passes instead
+/// Taking longer than the timer allows throws away whatever was proposed, and draws the penalty tiles
+/// Whatever happened is recorded in the sink, always ending with exactly one turn ending event
+pub fn take_turn(
+    player: &Player,
+    observation: &Observation,
+    prev_table: &PublicGameState,
+    timer: &TurnTimer,
+    sink: &mut dyn EventSink,
+) -> (Rack, PublicGameState) {
+    let prev_rack = &player.rack;
     let mut mut_rack = prev_rack.clone(); // or let mut rack = rack.clone?
     let mut mut_table = prev_table.clone();
     let mut placed_this_turn = false;
 
-    if !mut_rack.played_initial_meld {
-        if let Some(meld) = mut_rack.can_play_initial_meld() {
-            println!("Playing Initial Meld!");
-            // TODO remove meld from rack, must succeed -> Therefore it should not occur here and be part of return
-            // Very ambitious logic past self. It's true, but only if the program the way I theorize
-            mut_rack = mut_rack
-                .remove_meld(&meld)
-                .expect("Meld Could not be removed!");
-            mut_table.face_up = mut_table.face_up.place_new_sets(&meld.sets);
-            placed_this_turn = true;
-            println!("Table Now Has:\n{}", mut_table.face_up)
+    let started = timer.clock.now();
+    let proposal = player.strategy.propose_turn(observation);
+    if timer.is_over(started) {
+        // Nothing proposed is ever applied, so the table is already back to how it was
+        info!("Out of Time! Must Draw {} Tiles!", PENALTY_TILES);
+        let mut penalty = vec![];
+        while penalty.len() < PENALTY_TILES {
+            let Some((drawn, new_bones)) = mut_table.boneyard.draw_one() else {
+                break;
+            };
+            mut_rack.add_tile_to_rack(&drawn);
+            mut_table.boneyard = new_bones;
+            penalty.push(drawn);
         }
+        sink.record(&GameEvent::TimedOut {
+            player: player.info.clone(),
+            penalty,
+        });
+        return (mut_rack, mut_table);
     }
 
-    if mut_rack.played_initial_meld {
-        // can attempt to add new tiles to the table
-        if let Some((complete_sets, rack_without_sets)) = mut_rack.sets_on_rack() {
-            println!("Placing Complete Sets from Rack!");
-            mut_table.face_up = mut_table.face_up.place_new_sets(&complete_sets);
-            mut_rack = rack_without_sets;
-            placed_this_turn = true;
-            println!("Table Now Has:\n{}", mut_table.face_up)
-        }
+    if let Some((proposed_rack, proposed_face_up, via)) = proposal {
+        let proposed_table = PublicGameState {
+            face_up: proposed_face_up,
+            ..prev_table.clone()
+        };
+        match validate_turn(prev_rack, prev_table, &proposed_rack, &proposed_table) {
+            Ok(()) => {
+                // Rebuilt from the diff, exactly as a replay would, so the two always agree
+                let diff = TableDiff::between(&prev_table.face_up, &proposed_table.face_up);
+                let from_rack =
+                    TileSequenceType::of(&proposed_table.face_up).difference(&prev_table.face_up);
+                play_from_rack(&mut mut_rack, &from_rack).expect("Validated against the rack");
+                mut_table.face_up = diff.apply(&prev_table.face_up).expect("Diff of this table");
+                placed_this_turn = true;
 
-        if let Some((rack_after_placing, new_face_up)) = rearrange(&mut_rack, &mut_table.face_up) {
-            println!("Rearranged Face Up Tiles and Placing some from Rack!");
-            mut_table.face_up = new_face_up;
-            mut_rack = rack_after_placing;
-            placed_this_turn = true;
-            println!("Table Now Has:\n{}", mut_table.face_up)
+                let info = player.info.clone();
+                if !prev_rack.played_initial_meld {
+                    sink.record(&GameEvent::InitialMeldPlayed {
+                        player: info,
+                        sets: diff.added,
+                        via,
+                    });
+                } else if diff.removed.is_empty() {
+                    sink.record(&GameEvent::SetsPlaced {
+                        player: info,
+                        sets: diff.added,
+                        via,
+                    });
+                } else {
+                    for from in diff.jokers_retrieved() {
+                        sink.record(&GameEvent::JokerRetrieved {
+                            player: info.clone(),
+                            from,
+                        });
+                    }
+                    sink.record(&GameEvent::TableRearranged {
+                        player: info,
+                        from_rack,
+                        diff,
+                        via,
+                    });
+                }
+            }
+            Err(violation) => info!("Proposed Turn Rejected! {}", violation),
         }
     }
 
     if !placed_this_turn {
         // Have Not Placed Any Tiles This Turn, therefore MUST draw
-        println!("Must Draw from Boneyard!");
+        info!("Must Draw from Boneyard!");
 
         if let Some((drawn, new_bones)) = prev_table.boneyard.draw_one() {
             mut_rack.add_tile_to_rack(&drawn);
             mut_table.boneyard = new_bones;
+            sink.record(&GameEvent::TileDrawn {
+                player: player.info.clone(),
+                tile: drawn,
+            });
         } else {
-            //TODO technically this should not happen, but can if players do not play well or hold on forever
-            println!("\n--> All Tiles have been Drawn! Game Over!\n");
-            // TODO again, taking a shortcut here
-            // TODO LOOOOOL, yes a mega shortcut just reseting the "winner" to have an empty rack. Need to redo logic
-            mut_rack = Rack {
-                rack: vec![],
-                played_initial_meld: true,
-            }
+            // Nothing left to draw, the player simply passes and the rack stays the same
+            info!("Boneyard is Empty! Pass!");
+            sink.record(&GameEvent::Passed {
+                player: player.info.clone(),
+            });
         }
     }
 
     (mut_rack, mut_table)
 }
 
+/// Plays the game until someone empties their rack. If the boneyard has run out, and a full round
+/// goes by where nobody can play, the game is over and the player with the lowest rack wins
 pub fn main_game_loop(initial_state: GameState) -> GameOutcome {
+    main_game_loop_with_events(initial_state, &mut Discard)
+}
+
+/// Keeps every event for the players to look back on, before passing it on
+struct History<'a> {
+    events: Vec<GameEvent>,
+    /// What each player has seen, and how many of the events that covers
+    seen: BTreeMap<PlayerInfo, (usize, Arc<Vec<Seen>>)>,
+    sink: &'a mut dyn EventSink,
+}
+
+impl History<'_> {
+    /// Everything the player has seen so far. Only the events since they last looked are added,
+    /// in place unless a strategy is still holding on to an earlier observation
+    fn seen_by(&mut self, player: &PlayerInfo) -> Arc<Vec<Seen>> {
+        let (caught_up, seen) = self.seen.entry(player.clone()).or_default();
+        let new = self.events[*caught_up..].iter();
+        Arc::make_mut(seen).extend(new.map(|event| Seen::by(player, event)));
+        *caught_up = self.events.len();
+        seen.clone()
+    }
+}
+
+impl EventSink for History<'_> {
+    fn record(&mut self, event: &GameEvent) {
+        self.events.push(event.clone());
+        self.sink.record(event);
+    }
+}
+
+/// The main game loop, which also records every event of the game in the sink as it happens
+pub fn main_game_loop_with_events(
+    initial_state: GameState,
+    sink: &mut dyn EventSink,
+) -> GameOutcome {
+    let sink = &mut History {
+        events: vec![],
+        seen: BTreeMap::new(),
+        sink,
+    };
     let mut current_state = initial_state.clone();
+    sink.record(&GameEvent::GameStarted {
+        seed: current_state.seed,
+        num_players: current_state.players.len() as u8,
+        strategies: current_state
+            .players
+            .iter()
+            .map(|p| p.strategy.name().to_string())
+            .collect(),
+    });
+    for player in &current_state.players {
+        sink.record(&GameEvent::InitialDeal {
+            player: player.info.clone(),
+            tiles: player.rack.decompose(),
+        });
+    }
+
     let mut current_player = current_state.players.pop_front().expect("Lost players!");
+    let seats = current_state.players.len() + 1;
+    let mut passes_in_a_row = 0;
+    let mut turns = 0;
 
-    while !current_player.rack.is_empty() {
-        println!(
-            "{}'s Turn! Their rack: {}",
-            current_player.info, current_player.rack
+    while !current_player.rack.is_empty() && passes_in_a_row < seats {
+        info!(
+            "{}'s Turn ({})!",
+            current_player.info,
+            current_player.strategy.name()
         );
-        let (rack, table) = take_turn(&current_player.rack, &current_state.table);
+        // Only for watching bots, anyone playing along must not see the other racks
+        debug!("Their rack: {}", current_player.rack);
+        let observation = Observation::with_history(
+            &current_player,
+            &current_state.players,
+            &current_state.table,
+            sink.seen_by(&current_player.info),
+        );
+        let (rack, table) = take_turn(
+            &current_player,
+            &observation,
+            &current_state.table,
+            &current_state.timer,
+            sink,
+        );
+        turns += 1;
+        // Placing always takes tiles off the rack, and drawing always adds one, so same size is a pass
+        if rack.rack.len() == current_player.rack.rack.len() {
+            passes_in_a_row += 1;
+        } else {
+            passes_in_a_row = 0;
+        }
         let updated_player = Player {
-            info: current_player.info.clone(),
             rack,
+            ..current_player.clone()
         };
+        current_state.table = table;
         if updated_player.rack.is_empty() {
             current_player = updated_player;
             break;
         }
         current_state.players.push_back(updated_player);
-        current_state.table = table;
         current_player = current_state
             .players
             .pop_front()
@@ -106,15 +255,192 @@ pub fn main_game_loop(initial_state: GameState) -> GameOutcome {
     }
 
     //End Game, Compute Result
-    let winner = current_player;
-    println!("Game Over! {} Wins!", winner.info);
-
-    // TODO not sure that ordering worked, test this
-    let loser = current_state
-        .players
-        .iter()
-        .max()
-        .expect("Tie maybe?")
-        .clone();
-    GameOutcome { winner, loser }
+    let mut players = vec![current_player];
+    players.extend(current_state.players);
+    let end = if players[0].rack.is_empty() {
+        GameEnd::RackEmptied
+    } else {
+        info!("\n--> All Tiles have been Drawn, and Nobody can Play! Game Over!\n");
+        GameEnd::Blocked
+    };
+    let outcome = GameOutcome::new(players, turns, end);
+    info!("Game Over! {} Wins!", outcome.winner.info);
+    sink.record(&GameEvent::GameEnded {
+        end,
+        winner: outcome.winner.info.clone(),
+        turns,
+    });
+    outcome
+}
+
+#[cfg(test)]
+mod end_game_tests {
+    use super::*;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile;
+    use crate::domain::tiles::Tile::RegularTile;
+    use crate::game_loop::clock::ManualClock;
+    use crate::game_loop::events::replay;
+    use crate::game_loop::meta::{GameConfig, GameEnd};
+    use crate::game_loop::strategy::{default_strategy, MovePath};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    /// A game where the boneyard is already empty, and nobody can make their initial meld
+    fn stuck_game(racks: Vec<Vec<Tile>>) -> GameState {
+        let mut state = GameState::init_game(GameConfig {
+            num_players: racks.len() as u8,
+            seed: Some(5),
+            ..GameConfig::default()
+        });
+        state.table.boneyard = Boneyard { bones: vec![] };
+        for (player, rack) in state.players.iter_mut().zip(racks) {
+            player.rack = Rack {
+                rack,
+                played_initial_meld: false,
+            };
+            player.strategy = default_strategy();
+        }
+        state
+    }
+
+    #[test]
+    fn passes_when_boneyard_is_empty() {
+        let state = stuck_game(vec![vec![RegularTile(Red, One)]]);
+        let player = &state.players[0];
+        let mut events: Vec<GameEvent> = vec![];
+        let seen = Observation::of(player, [], &state.table, &[]);
+        let (rack, table) = take_turn(player, &seen, &state.table, &state.timer, &mut events);
+        assert_eq!(rack, player.rack);
+        assert!(table.boneyard.bones.is_empty());
+        assert_eq!(
+            events,
+            vec![GameEvent::Passed {
+                player: player.info.clone()
+            }]
+        );
+    }
+
+    #[test]
+    fn lowest_rack_wins_when_nobody_can_play() {
+        let outcome = main_game_loop(stuck_game(vec![
+            vec![RegularTile(Red, Nine), RegularTile(Blue, One)],
+            vec![RegularTile(Red, Three)],
+            vec![RegularTile(Black, Thirteen)],
+        ]));
+        assert_eq!(outcome.winner.info, PlayerInfo::of(&"2".to_string()));
+        assert_eq!(outcome.loser.info, PlayerInfo::of(&"3".to_string()));
+        assert_eq!(outcome.end, GameEnd::Blocked);
+        assert_eq!(outcome.turns, 3);
+        assert!(!outcome.is_tie());
+
+        let ranked: Vec<(usize, u16, i32)> = outcome
+            .standings
+            .iter()
+            .map(|s| (s.rank, s.rack_score.as_u16(), s.delta))
+            .collect();
+        assert_eq!(ranked, vec![(1, 3, 17), (2, 10, -7), (3, 13, -10)]);
+    }
+
+    #[test]
+    fn equal_racks_tie() {
+        let outcome = main_game_loop(stuck_game(vec![
+            vec![RegularTile(Red, Five)],
+            vec![RegularTile(Red, Nine)],
+            vec![RegularTile(Blue, Two), RegularTile(Orange, Three)],
+        ]));
+        assert_eq!(outcome.winner.info, PlayerInfo::of(&"1".to_string()));
+        assert!(outcome.is_tie());
+        let ranked: Vec<(String, usize, i32)> = outcome
+            .standings
+            .iter()
+            .map(|s| (s.player.info.to_string(), s.rank, s.delta))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("Player 1".to_string(), 1, 2),
+                ("Player 3".to_string(), 1, 2),
+                ("Player 2".to_string(), 3, -4)
+            ]
+        );
+        assert!(outcome
+            .to_string()
+            .contains("Tie between Player 1 and Player 3"));
+    }
+
+    /// Plays whatever the default strategy would, but takes this long to think about it
+    struct Slow {
+        clock: Arc<ManualClock>,
+        thinking: Duration,
+    }
+
+    impl Strategy for Slow {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+            default_strategy().rearrange(rack, face_up)
+        }
+
+        fn propose_turn(
+            &self,
+            observation: &Observation,
+        ) -> Option<(Rack, FaceUpTiles, Vec<MovePath>)> {
+            self.clock.advance(self.thinking);
+            default_strategy().propose_turn(observation)
+        }
+    }
+
+    fn timed_turn(thinking: Duration) -> (GameState, Vec<GameEvent>, Rack, PublicGameState) {
+        let clock = Arc::new(ManualClock::default());
+        let mut state = GameState::init_game(GameConfig {
+            num_players: 2,
+            seed: Some(5),
+            turn_limit: Some(Duration::from_secs(60)),
+            clock: clock.clone(),
+            ..GameConfig::default()
+        });
+        state.players[0].rack = "[R10 R11 R12 K1]".parse().unwrap();
+        state.players[0].strategy = Arc::new(Slow { clock, thinking });
+        let mut events: Vec<GameEvent> = vec![];
+        let player = &state.players[0];
+        let seen = Observation::of(player, [&state.players[1]], &state.table, &[]);
+        let (rack, table) = take_turn(player, &seen, &state.table, &state.timer, &mut events);
+        (state, events, rack, table)
+    }
+
+    #[test]
+    fn plays_as_usual_within_the_turn_limit() {
+        let (_, events, rack, table) = timed_turn(Duration::from_secs(59));
+        assert_eq!(format!("{:#}", rack), "[K1]");
+        assert_eq!(format!("{:#}", table.face_up), "R10-R12");
+        assert!(matches!(events[0], GameEvent::InitialMeldPlayed { .. }));
+    }
+
+    #[test]
+    fn over_the_turn_limit_draws_the_penalty() {
+        let (state, events, rack, table) = timed_turn(Duration::from_secs(61));
+        let bones = &state.table.boneyard.bones;
+        let penalty: Vec<Tile> = bones.iter().rev().take(PENALTY_TILES).cloned().collect();
+        assert_eq!(
+            events,
+            vec![GameEvent::TimedOut {
+                player: state.players[0].info.clone(),
+                penalty: penalty.clone()
+            }]
+        );
+        assert!(table.face_up.sets.is_empty());
+        assert_eq!(table.boneyard.bones.len(), bones.len() - PENALTY_TILES);
+        assert_eq!(rack.rack.len(), 4 + PENALTY_TILES);
+        for tile in &penalty {
+            assert!(rack.rack.contains(tile));
+        }
+
+        let replayed = replay(&state, &events).unwrap();
+        assert_eq!(replayed[0].players.back().unwrap().rack, rack);
+        assert_eq!(replayed[0].table.boneyard, table.boneyard);
+    }
 }
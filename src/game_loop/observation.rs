@@ -0,0 +1,290 @@
+use crate::domain::player::info::PlayerInfo;
+use crate::domain::player::rack::Rack;
+use crate::domain::player::Player;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::Decompose;
+use crate::game_loop::events::GameEvent;
+use crate::game_loop::state::PublicGameState;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+/// Everything one player is allowed to know at the start of their turn, and all that a strategy
+/// or a client of the server is ever given. The boneyard is only a count, so the order of the
+/// tiles still to be drawn stays hidden, as do the tiles on everyone else's rack.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    pub rack: Rack,
+    pub face_up: FaceUpTiles,
+    /// How many tiles are left to draw
+    pub boneyard: usize,
+    /// Everyone else, in the order they play after this player
+    pub opponents: Vec<Opponent>,
+    /// Everything that happened before this turn, as this player saw it. Shared with the game
+    /// loop, which only adds what happened since the player's last turn
+    pub history: Arc<Vec<Seen>>,
+}
+
+/// What can be seen of another player from across the table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Opponent {
+    pub info: PlayerInfo,
+    /// How many tiles are on their rack, but not which
+    pub tiles: usize,
+    pub played_initial_meld: bool,
+}
+
+/// A single event, as one player saw it. Tiles which went onto anyone else's rack stay hidden,
+/// and so does the seed, which would give away every rack and the order of the boneyard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Seen {
+    Event(GameEvent),
+    Started {
+        num_players: u8,
+        strategies: Vec<String>,
+    },
+    OtherDealt {
+        player: PlayerInfo,
+        tiles: usize,
+    },
+    OtherDrew {
+        player: PlayerInfo,
+    },
+    OtherTimedOut {
+        player: PlayerInfo,
+        tiles: usize,
+    },
+}
+
+impl Seen {
+    pub fn by(seat: &PlayerInfo, event: &GameEvent) -> Seen {
+        match event {
+            GameEvent::GameStarted {
+                num_players,
+                strategies,
+                ..
+            } => Seen::Started {
+                num_players: *num_players,
+                strategies: strategies.clone(),
+            },
+            GameEvent::InitialDeal { player, tiles } if player != seat => Seen::OtherDealt {
+                player: player.clone(),
+                tiles: tiles.len(),
+            },
+            GameEvent::TileDrawn { player, .. } if player != seat => Seen::OtherDrew {
+                player: player.clone(),
+            },
+            GameEvent::TimedOut { player, penalty } if player != seat => Seen::OtherTimedOut {
+                player: player.clone(),
+                tiles: penalty.len(),
+            },
+            _ => Seen::Event(event.clone()),
+        }
+    }
+}
+
+impl fmt::Display for Seen {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Seen::Event(event) if f.alternate() => write!(f, "{:#}", event),
+            Seen::Event(event) => write!(f, "{}", event),
+            Seen::Started { num_players, .. } => {
+                write!(f, "Game Started with {} players", num_players)
+            }
+            Seen::OtherDealt { player, tiles } => write!(f, "{} was dealt {} tiles", player, tiles),
+            Seen::OtherDrew { player } => write!(f, "{} drew a tile", player),
+            Seen::OtherTimedOut { player, tiles } => write!(
+                f,
+                "{} ran out of time and drew {} penalty tiles",
+                player, tiles
+            ),
+        }
+    }
+}
+
+impl Observation {
+    /// What the player sees, with everyone else given in the order they play after them
+    pub fn of<'a>(
+        player: &Player,
+        others: impl IntoIterator<Item = &'a Player>,
+        table: &PublicGameState,
+        history: &[GameEvent],
+    ) -> Observation {
+        let seen = history.iter().map(|e| Seen::by(&player.info, e)).collect();
+        Observation::with_history(player, others, table, Arc::new(seen))
+    }
+
+    /// The same, with the history already as the player saw it
+    pub fn with_history<'a>(
+        player: &Player,
+        others: impl IntoIterator<Item = &'a Player>,
+        table: &PublicGameState,
+        history: Arc<Vec<Seen>>,
+    ) -> Observation {
+        Observation {
+            rack: player.rack.clone(),
+            face_up: table.face_up.clone(),
+            boneyard: table.boneyard.bones.len(),
+            opponents: others
+                .into_iter()
+                .map(|other| Opponent {
+                    info: other.info.clone(),
+                    tiles: other.rack.decompose().len(),
+                    played_initial_meld: other.rack.played_initial_meld,
+                })
+                .collect(),
+            history,
+        }
+    }
+
+    /// Only a rack and a table, with nobody else playing and nothing left to draw
+    pub fn new(rack: &Rack, face_up: &FaceUpTiles) -> Observation {
+        Observation {
+            rack: rack.clone(),
+            face_up: face_up.clone(),
+            boneyard: 0,
+            opponents: vec![],
+            history: Arc::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod observation_tests {
+    use super::*;
+    use crate::game_loop::main_game_loop_with_events;
+    use crate::game_loop::meta::GameConfig;
+    use crate::game_loop::state::GameState;
+    use crate::game_loop::strategy::{default_strategy, MovePath, Strategy};
+    use std::sync::Mutex;
+
+    #[test]
+    fn only_sees_their_own_tiles() {
+        let state = GameState::init_game(GameConfig {
+            num_players: 3,
+            seed: Some(21),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        main_game_loop_with_events(state.clone(), &mut events);
+
+        let me = &state.players[1];
+        let others = state
+            .players
+            .iter()
+            .skip(2)
+            .chain(state.players.iter().take(1));
+        let seen = Observation::of(me, others, &state.table, &events);
+        assert_eq!(seen.rack, me.rack);
+        assert_eq!(seen.boneyard, state.table.boneyard.bones.len());
+        let opponents: Vec<(String, usize, bool)> = seen
+            .opponents
+            .iter()
+            .map(|o| (o.info.to_string(), o.tiles, o.played_initial_meld))
+            .collect();
+        assert_eq!(
+            opponents,
+            vec![
+                ("Player 3".to_string(), 14, false),
+                ("Player 1".to_string(), 14, false)
+            ]
+        );
+
+        assert_eq!(seen.history.len(), events.len());
+        for seen in seen.history.iter() {
+            match seen {
+                Seen::Event(GameEvent::InitialDeal { player, .. })
+                | Seen::Event(GameEvent::TileDrawn { player, .. })
+                | Seen::Event(GameEvent::TimedOut { player, .. }) => assert_eq!(*player, me.info),
+                Seen::OtherDealt { player, .. }
+                | Seen::OtherDrew { player }
+                | Seen::OtherTimedOut { player, .. } => assert_ne!(*player, me.info),
+                Seen::Event(event) => assert!(!matches!(event, GameEvent::GameStarted { .. })),
+                Seen::Started { .. } => {}
+            }
+        }
+        assert!(seen
+            .history
+            .iter()
+            .any(|s| matches!(s, Seen::OtherDrew { .. })));
+    }
+
+    /// Plays like the default strategy, remembering the history it was shown every turn
+    #[derive(Default)]
+    struct Watcher(Mutex<Vec<Arc<Vec<Seen>>>>);
+
+    impl Strategy for Watcher {
+        fn name(&self) -> &str {
+            "watcher"
+        }
+
+        fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+            default_strategy().rearrange(rack, face_up)
+        }
+
+        fn propose_turn(
+            &self,
+            observation: &Observation,
+        ) -> Option<(Rack, FaceUpTiles, Vec<MovePath>)> {
+            self.0.lock().unwrap().push(observation.history.clone());
+            default_strategy().propose_turn(observation)
+        }
+    }
+
+    #[test]
+    fn history_during_the_game_is_everything_so_far() {
+        let watchers: Vec<Arc<Watcher>> = (0..3).map(|_| Arc::default()).collect();
+        let state = GameState::init_game(GameConfig {
+            num_players: 3,
+            seed: Some(34),
+            strategies: watchers
+                .iter()
+                .map(|w| w.clone() as Arc<dyn Strategy>)
+                .collect(),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        main_game_loop_with_events(state.clone(), &mut events);
+
+        for (me, watcher) in state.players.iter().zip(&watchers) {
+            let shown = watcher.0.lock().unwrap();
+            assert!(shown.len() > 1);
+            assert!(shown.windows(2).all(|w| w[0].len() < w[1].len()));
+            for history in shown.iter() {
+                let expected: Vec<Seen> = events[..history.len()]
+                    .iter()
+                    .map(|e| Seen::by(&me.info, e))
+                    .collect();
+                assert_eq!(**history, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn never_sees_the_seed() {
+        let seed = 8_675_309_000_123;
+        let state = GameState::init_game(GameConfig {
+            num_players: 3,
+            seed: Some(seed),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        main_game_loop_with_events(state.clone(), &mut events);
+
+        for (seat, me) in state.players.iter().enumerate() {
+            let others = state.players.iter().skip(seat + 1);
+            let seen = Observation::of(me, others, &state.table, &events);
+            assert_eq!(
+                seen.history[0],
+                Seen::Started {
+                    num_players: 3,
+                    strategies: vec!["human_like".to_string(); 3]
+                }
+            );
+            let json = serde_json::to_string(&seen).unwrap();
+            assert!(!json.contains(&seed.to_string()));
+            assert!(!json.contains("seed"));
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::game_loop::state::GameState;
+use serde::{Deserialize, Serialize};
+use std::fmt::Formatter;
+use std::path::Path;
+use std::{fmt, fs, io};
+
+/// Bumped whenever the saved format changes, so an old save is rejected instead of misread
+pub const SAVE_VERSION: u32 = 1;
+
+/// The saved form of a whole game, everything needed to carry on exactly where it left off
+#[derive(Serialize)]
+struct SavedGame<'a> {
+    version: u32,
+    game: &'a GameState,
+}
+
+#[derive(Deserialize)]
+struct LoadedGame {
+    version: u32,
+    game: GameState,
+}
+
+/// The reasons a saved game could not be loaded
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// Not JSON, or not a game
+    Json(serde_json::Error),
+    /// Saved by a different version of rustikub
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "Could not read the saved game: {}", e),
+            LoadError::Json(e) => write!(f, "Not a valid saved game: {}", e),
+            LoadError::UnsupportedVersion(v) => write!(
+                f,
+                "Saved game is version {}, only version {} can be loaded",
+                v, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+impl From<serde_json::Error> for LoadError {
+    fn from(e: serde_json::Error) -> Self {
+        LoadError::Json(e)
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> Self {
+        LoadError::Io(e)
+    }
+}
+
+impl GameState {
+    /// The whole game as versioned JSON. The boneyard stays in order, every joker stays where it
+    /// was on the table, and the rng carries on from the same point, so a loaded game plays out
+    /// exactly as this one would have
+    pub fn to_json(&self) -> String {
+        let saved = SavedGame {
+            version: SAVE_VERSION,
+            game: self,
+        };
+        serde_json::to_string_pretty(&saved).expect("Game state is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<GameState, LoadError> {
+        // The version is checked first, since a different version may not parse as a game at all
+        let value: serde_json::Value = serde_json::from_str(json)?;
+        let version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+        if version != SAVE_VERSION {
+            return Err(LoadError::UnsupportedVersion(version));
+        }
+        let loaded: LoadedGame = serde_json::from_value(value)?;
+        Ok(loaded.game)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_json())
+    }
+
+    pub fn load(path: &Path) -> Result<GameState, LoadError> {
+        GameState::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod save_tests {
+    use super::*;
+    use crate::domain::sets::group::Group;
+    use crate::domain::sets::run::Run;
+    use crate::domain::sets::Set;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+    use crate::game_loop::main_game_loop;
+    use crate::game_loop::meta::GameConfig;
+    use crate::game_loop::strategy::all_strategies;
+
+    fn new_game(seed: u64) -> GameState {
+        GameState::init_game(GameConfig {
+            num_players: 3,
+            seed: Some(seed),
+            strategies: all_strategies(),
+            ..GameConfig::default()
+        })
+    }
+
+    fn assert_same_game(first: &GameState, second: &GameState) {
+        assert_eq!(first.seed, second.seed);
+        assert_eq!(first.rng, second.rng);
+        assert_eq!(first.table.boneyard, second.table.boneyard);
+        assert_eq!(first.table.face_up, second.table.face_up);
+        assert_eq!(first.players, second.players);
+    }
+
+    #[test]
+    fn round_trip_keeps_jokers_in_place() {
+        let mut game = new_game(8);
+        game.table.face_up.sets = vec![
+            Set::Run(
+                Run::parse(&[JokersWild, RegularTile(Red, Two), RegularTile(Red, Three)]).unwrap(),
+            ),
+            Set::Run(
+                Run::parse(&[
+                    RegularTile(Blue, Five),
+                    JokersWild,
+                    RegularTile(Blue, Seven),
+                ])
+                .unwrap(),
+            ),
+            Set::Run(
+                Run::parse(&[
+                    RegularTile(Black, Eleven),
+                    RegularTile(Black, Twelve),
+                    JokersWild,
+                ])
+                .unwrap(),
+            ),
+            Set::Group(Group::of(Nine, &vec![Red, Orange, Black]).unwrap()),
+        ];
+        let loaded = GameState::from_json(&game.to_json()).unwrap();
+        assert_same_game(&game, &loaded);
+        // Saving again gives exactly the same JSON
+        assert_eq!(game.to_json(), loaded.to_json());
+    }
+
+    #[test]
+    fn resumed_game_plays_out_the_same() {
+        let game = new_game(21);
+        let loaded = GameState::from_json(&game.to_json()).unwrap();
+        assert_eq!(main_game_loop(game), main_game_loop(loaded));
+    }
+
+    #[test]
+    fn save_and_load_file() {
+        let game = new_game(4);
+        let path = std::env::temp_dir().join(format!("rustikub_save_{}.json", std::process::id()));
+        game.save(&path).unwrap();
+        let loaded = GameState::load(&path);
+        fs::remove_file(&path).unwrap();
+        assert_same_game(&game, &loaded.unwrap());
+    }
+
+    #[test]
+    fn rejects_other_versions_and_invalid_sets() {
+        let json = new_game(1).to_json();
+        let newer = json.replacen("\"version\": 1", "\"version\": 2", 1);
+        assert!(matches!(
+            GameState::from_json(&newer),
+            Err(LoadError::UnsupportedVersion(2))
+        ));
+
+        let mut game = new_game(1);
+        game.table.face_up.sets = vec![Set::Run(Run::of(One, Red, 3).unwrap())];
+        let mut broken: serde_json::Value = serde_json::from_str(&game.to_json()).unwrap();
+        broken["game"]["table"]["face_up"]["sets"][0]["Run"][2] =
+            serde_json::json!({ "RegularTile": ["Red", "Five"] });
+        assert!(matches!(
+            GameState::from_json(&broken.to_string()),
+            Err(LoadError::Json(_))
+        ));
+        assert!(matches!(
+            GameState::from_json("not json"),
+            Err(LoadError::Json(_))
+        ));
+    }
+}
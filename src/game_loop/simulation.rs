@@ -0,0 +1,456 @@
+use crate::domain::player::info::PlayerInfo;
+use crate::domain::sets::Set;
+use crate::domain::tiles::Tile;
+use crate::domain::Decompose;
+use crate::game_loop::events::GameEvent;
+use crate::game_loop::main_game_loop_with_events;
+use crate::game_loop::meta::{GameConfig, GameEnd, GameOutcome, GameRng};
+use crate::game_loop::state::GameState;
+use crate::game_loop::strategy::Strategy;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+/// Final rack scores are counted in buckets this many points wide
+pub const RACK_SCORE_BUCKET: u16 = 10;
+
+/// Information used to control a simulation, many games played at once with nobody watching
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub games: u32,
+    /// Each game is played with the next of these in turn, so every table size gets an even share.
+    /// If empty, every game has as many players as GameConfig does by default
+    pub player_counts: Vec<u8>,
+    /// Strategies are handed out in seating order as in GameConfig, but starting one strategy
+    /// further along each time a game of the same size comes around, so each strategy takes its
+    /// turn in every seat.
+    /// If empty, every player uses the default strategy
+    pub strategies: Vec<Arc<dyn Strategy>>,
+    /// Seeds the whole simulation, and each game gets its own seed derived from it
+    pub seed: Option<u64>,
+    /// How many games are played at the same time
+    pub threads: usize,
+}
+
+impl Default for SimulationConfig {
+    /// One game at a time for each CPU core
+    fn default() -> Self {
+        SimulationConfig {
+            games: 1000,
+            player_counts: vec![4],
+            strategies: vec![],
+            seed: None,
+            threads: thread::available_parallelism().map_or(1, |cores| cores.get()),
+        }
+    }
+}
+
+/// How one player did in one game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeatStats {
+    pub strategy: String,
+    /// Counting from 0, where seat 0 takes the first turn
+    pub seat: usize,
+    /// Sharing first place counts as a win
+    pub won: bool,
+    pub rack_score: u16,
+    /// Which of their own turns the initial meld was played on, counting from 1
+    pub melded_on: Option<u32>,
+    /// Put on the table from the rack
+    pub jokers_played: u32,
+    pub jokers_retrieved: u32,
+    /// Still on the rack when the game ended
+    pub jokers_left: u32,
+}
+
+/// A single game, boiled down to what goes into a SimulationReport
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameStats {
+    pub turns: u32,
+    pub end: GameEnd,
+    /// Every last tile was drawn, whether or not someone went out afterwards
+    pub boneyard_ran_out: bool,
+    /// In seating order
+    pub seats: Vec<SeatStats>,
+}
+
+impl GameStats {
+    /// Goes through the events of a game played from start, which ended in outcome
+    pub fn of(start: &GameState, events: &[GameEvent], outcome: &GameOutcome) -> GameStats {
+        let seat_of = |player: &PlayerInfo| {
+            start
+                .players
+                .iter()
+                .position(|p| p.info == *player)
+                .expect("Only players in the game take part in it")
+        };
+        let jokers = |tiles: Vec<Tile>| tiles.iter().filter(|t| t.is_joker()).count() as u32;
+        let jokers_in = |sets: &[Set]| jokers(sets.iter().flat_map(|s| s.decompose()).collect());
+
+        let mut seats: Vec<SeatStats> = start
+            .players
+            .iter()
+            .enumerate()
+            .map(|(seat, player)| SeatStats {
+                strategy: player.strategy.name().to_string(),
+                seat,
+                won: false,
+                rack_score: 0,
+                melded_on: None,
+                jokers_played: 0,
+                jokers_retrieved: 0,
+                jokers_left: 0,
+            })
+            .collect();
+        // Turns go around the table in seating order, so the turn says whose own turn it is
+        let mut turn = 0;
+        let mut drawn = 0;
+        for event in events {
+            match event {
+                GameEvent::TileDrawn { .. } => drawn += 1,
+                GameEvent::TimedOut { penalty, .. } => drawn += penalty.len(),
+                GameEvent::InitialMeldPlayed { player, sets, .. } => {
+                    let seat = &mut seats[seat_of(player)];
+                    seat.melded_on = Some(turn / start.players.len() as u32 + 1);
+                    seat.jokers_played += jokers_in(sets);
+                }
+                GameEvent::SetsPlaced { player, sets, .. } => {
+                    seats[seat_of(player)].jokers_played += jokers_in(sets);
+                }
+                GameEvent::TableRearranged {
+                    player, from_rack, ..
+                } => seats[seat_of(player)].jokers_played += jokers(from_rack.clone()),
+                GameEvent::JokerRetrieved { player, .. } => {
+                    seats[seat_of(player)].jokers_retrieved += 1
+                }
+                _ => {}
+            }
+            if event.ends_turn() {
+                turn += 1;
+            }
+        }
+        for standing in &outcome.standings {
+            let seat = &mut seats[seat_of(&standing.player.info)];
+            seat.won = standing.rank == 1;
+            seat.rack_score = standing.rack_score.as_u16();
+            seat.jokers_left = jokers(standing.player.rack.decompose());
+        }
+
+        GameStats {
+            turns: outcome.turns,
+            end: outcome.end,
+            boneyard_ran_out: drawn == start.table.boneyard.bones.len(),
+            seats,
+        }
+    }
+}
+
+/// How often a strategy won from one seat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SeatRecord {
+    pub played: u32,
+    pub won: u32,
+}
+
+/// Everything a strategy did, added up over every game and seat it played
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StrategyStats {
+    /// Counting from seat 0
+    pub seats: Vec<SeatRecord>,
+    /// How many times the initial meld was played, and the sum of the turns it was played on
+    pub melded: u32,
+    pub meld_turns: u64,
+    pub jokers_played: u32,
+    pub jokers_retrieved: u32,
+    pub jokers_left: u32,
+    pub rack_scores: u64,
+}
+
+impl StrategyStats {
+    /// Over every seat
+    pub fn total(&self) -> SeatRecord {
+        self.seats
+            .iter()
+            .fold(SeatRecord::default(), |sum, seat| SeatRecord {
+                played: sum.played + seat.played,
+                won: sum.won + seat.won,
+            })
+    }
+}
+
+/// The statistics of a whole simulation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SimulationReport {
+    /// Simulating again with this seed gives the same report
+    pub seed: u64,
+    pub games: u32,
+    pub turns: u64,
+    pub boneyard_ran_out: u32,
+    /// The boneyard ran out and then nobody could play, see GameEnd::Blocked
+    pub blocked: u32,
+    /// By the name of the strategy
+    pub strategies: BTreeMap<String, StrategyStats>,
+    /// How many players finished with a rack worth this much, by the bottom of each
+    /// RACK_SCORE_BUCKET wide bucket
+    pub rack_scores: BTreeMap<u16, u32>,
+}
+
+impl SimulationReport {
+    pub fn add(&mut self, game: &GameStats) {
+        self.games += 1;
+        self.turns += game.turns as u64;
+        self.boneyard_ran_out += game.boneyard_ran_out as u32;
+        self.blocked += (game.end == GameEnd::Blocked) as u32;
+        for seat in &game.seats {
+            let stats = self.strategies.entry(seat.strategy.clone()).or_default();
+            if stats.seats.len() <= seat.seat {
+                stats.seats.resize(seat.seat + 1, SeatRecord::default());
+            }
+            stats.seats[seat.seat].played += 1;
+            stats.seats[seat.seat].won += seat.won as u32;
+            if let Some(turn) = seat.melded_on {
+                stats.melded += 1;
+                stats.meld_turns += turn as u64;
+            }
+            stats.jokers_played += seat.jokers_played;
+            stats.jokers_retrieved += seat.jokers_retrieved;
+            stats.jokers_left += seat.jokers_left;
+            stats.rack_scores += seat.rack_score as u64;
+            let bucket = seat.rack_score / RACK_SCORE_BUCKET * RACK_SCORE_BUCKET;
+            *self.rack_scores.entry(bucket).or_default() += 1;
+        }
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        self.turns as f64 / self.games.max(1) as f64
+    }
+}
+
+fn percent(part: u32, whole: u32) -> f64 {
+    100.0 * part as f64 / whole.max(1) as f64
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Simulation Seed: {}", self.seed)?;
+        writeln!(
+            f,
+            "{} games, {:.1} turns on average. The boneyard ran out in {:.1}% of games, \
+             and {:.1}% ended with nobody able to play",
+            self.games,
+            self.average_turns(),
+            percent(self.boneyard_ran_out, self.games),
+            percent(self.blocked, self.games)
+        )?;
+
+        let seats = self.strategies.values().map(|s| s.seats.len()).max();
+        write!(f, "\n{:<28}{:>7}{:>7}", "Strategy", "Games", "Win%")?;
+        for seat in 1..=seats.unwrap_or(0) {
+            write!(f, "{:>8}", format!("Seat {}", seat))?;
+        }
+        writeln!(f)?;
+        for (name, stats) in &self.strategies {
+            let total = stats.total();
+            write!(
+                f,
+                "{:<28}{:>7}{:>7.1}",
+                name,
+                total.played,
+                percent(total.won, total.played)
+            )?;
+            for seat in &stats.seats {
+                match seat.played {
+                    0 => write!(f, "{:>8}", "-")?,
+                    _ => write!(f, "{:>8.1}", percent(seat.won, seat.played))?,
+                }
+            }
+            writeln!(f)?;
+        }
+
+        // Everything from here on is per game played
+        writeln!(
+            f,
+            "\n{:<28}{:>8}{:>11}{:>9}{:>11}{:>7}{:>7}",
+            "Strategy", "Melded%", "Meld turn", "Jokers", "Retrieved", "Left", "Rack"
+        )?;
+        for (name, stats) in &self.strategies {
+            let played = stats.total().played.max(1) as f64;
+            writeln!(
+                f,
+                "{:<28}{:>8.1}{:>11.1}{:>9.2}{:>11.2}{:>7.2}{:>7.1}",
+                name,
+                percent(stats.melded, stats.total().played),
+                stats.meld_turns as f64 / stats.melded.max(1) as f64,
+                stats.jokers_played as f64 / played,
+                stats.jokers_retrieved as f64 / played,
+                stats.jokers_left as f64 / played,
+                stats.rack_scores as f64 / played
+            )?;
+        }
+
+        writeln!(f, "\nFinal Rack Scores:")?;
+        let most = self.rack_scores.values().max().copied().unwrap_or(0);
+        for (bucket, count) in &self.rack_scores {
+            let bar = "#".repeat((40 * *count as usize).div_ceil(most.max(1) as usize));
+            let range = format!("{}-{}", bucket, bucket + RACK_SCORE_BUCKET - 1);
+            writeln!(f, "{:>8}{:>8} {}", range, count, bar)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays every game of the simulation, spread over the given number of threads. Each game is
+/// seeded up front, so the report is the same for the same seed, however many threads there are
+pub fn simulate(conf: SimulationConfig) -> SimulationReport {
+    let seed = conf.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut simulation_rng = GameRng::seed_from_u64(seed);
+    let seeds: Vec<u64> = (0..conf.games).map(|_| simulation_rng.gen()).collect();
+    let next = AtomicUsize::new(0);
+
+    let played: Vec<GameStats> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..conf.threads.max(1))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut played = vec![];
+                    loop {
+                        let game = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(seed) = seeds.get(game) else {
+                            return played;
+                        };
+                        played.push(play_one(&conf, game, *seed));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("A simulated game panicked"))
+            .collect()
+    });
+
+    let mut report = SimulationReport {
+        seed,
+        ..SimulationReport::default()
+    };
+    for game in &played {
+        report.add(game);
+    }
+    report
+}
+
+fn play_one(conf: &SimulationConfig, game: usize, seed: u64) -> GameStats {
+    let num_players = match conf.player_counts.len() {
+        0 => GameConfig::default().num_players,
+        len => conf.player_counts[game % len],
+    };
+    // How many games of this size came before
+    let round = game / conf.player_counts.len().max(1);
+    let strategies: Vec<Arc<dyn Strategy>> = match conf.strategies.len() {
+        0 => vec![],
+        len => (0..num_players as usize)
+            .map(|seat| conf.strategies[(round + seat) % len].clone())
+            .collect(),
+    };
+    let start = GameState::init_game(GameConfig {
+        num_players,
+        seed: Some(seed),
+        strategies,
+        ..GameConfig::default()
+    });
+    let mut events: Vec<GameEvent> = vec![];
+    let outcome = main_game_loop_with_events(start.clone(), &mut events);
+    GameStats::of(&start, &events, &outcome)
+}
+
+#[cfg(test)]
+mod simulation_tests {
+    use super::*;
+    use crate::domain::player::rack::Rack;
+    use crate::domain::table::boneyard::Boneyard;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+    use crate::game_loop::strategy::{HumanLike, PlaceNewTilesSimple};
+
+    fn conf(threads: usize) -> SimulationConfig {
+        SimulationConfig {
+            games: 6,
+            player_counts: vec![2, 3],
+            strategies: vec![Arc::new(HumanLike), Arc::new(PlaceNewTilesSimple)],
+            seed: Some(8),
+            threads,
+        }
+    }
+
+    #[test]
+    fn same_seed_same_report_on_any_number_of_threads() {
+        let report = simulate(conf(3));
+        assert_eq!(report, simulate(conf(1)));
+        assert_eq!(report.seed, 8);
+        assert_eq!(report.games, 6);
+        let other = SimulationConfig {
+            seed: Some(9),
+            ..conf(3)
+        };
+        assert_ne!(report, simulate(other));
+    }
+
+    #[test]
+    fn every_player_of_every_game_is_counted() {
+        let report = simulate(conf(2));
+        let records: Vec<SeatRecord> = report.strategies.values().map(|s| s.total()).collect();
+        assert_eq!(
+            records.iter().map(|r| r.played).sum::<u32>(),
+            2 + 3 + 2 + 3 + 2 + 3
+        );
+        assert!(records.iter().map(|r| r.won).sum::<u32>() >= report.games);
+        assert_eq!(report.rack_scores.values().sum::<u32>(), 15);
+        assert!(report.boneyard_ran_out >= report.blocked);
+
+        // Strategies take turns in every seat, and only 3 player games have a third seat
+        let names: Vec<&String> = report.strategies.keys().collect();
+        assert_eq!(names, vec!["human_like", "place_new_tiles_simple"]);
+        for stats in report.strategies.values() {
+            assert_eq!(stats.seats.len(), 3);
+            assert!(stats.seats.iter().all(|seat| seat.played > 0));
+            assert!(stats.melded <= stats.total().played);
+        }
+        let third_seats: u32 = report.strategies.values().map(|s| s.seats[2].played).sum();
+        assert_eq!(third_seats, 3);
+    }
+
+    #[test]
+    fn nobody_could_play_from_the_start() {
+        let mut start = GameState::init_game(GameConfig {
+            num_players: 2,
+            seed: Some(4),
+            ..GameConfig::default()
+        });
+        start.table.boneyard = Boneyard { bones: vec![] };
+        start.players[0].rack = Rack {
+            rack: vec![RegularTile(Red, Nine), JokersWild],
+            played_initial_meld: false,
+        };
+        start.players[1].rack = Rack {
+            rack: vec![RegularTile(Blue, Two)],
+            played_initial_meld: false,
+        };
+        let mut events: Vec<GameEvent> = vec![];
+        let outcome = main_game_loop_with_events(start.clone(), &mut events);
+
+        let stats = GameStats::of(&start, &events, &outcome);
+        assert_eq!(stats.end, GameEnd::Blocked);
+        assert!(stats.boneyard_ran_out);
+        assert_eq!(stats.turns, 2);
+        let seats: Vec<(usize, bool, u16, Option<u32>, u32)> = stats
+            .seats
+            .iter()
+            .map(|s| (s.seat, s.won, s.rack_score, s.melded_on, s.jokers_left))
+            .collect();
+        assert_eq!(seats, vec![(0, false, 39, None, 1), (1, true, 2, None, 0)]);
+    }
+}
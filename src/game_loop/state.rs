@@ -7,31 +7,43 @@ use crate::domain::tiles::color::Color::*;
 use crate::domain::tiles::number::Number;
 use crate::domain::tiles::number::Number::*;
 use crate::domain::tiles::Tile;
-use crate::game_loop::meta::GameConfig;
+use crate::game_loop::clock::TurnTimer;
 use crate::game_loop::meta::GameOutcome;
+use crate::game_loop::meta::{GameConfig, GameRng};
+use crate::game_loop::strategy::default_strategy;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use Tile::RegularTile;
 
 /// Represents the publicly known state of a single game of rummikub
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PublicGameState {
     pub boneyard: Boneyard,
     pub face_up: FaceUpTiles,
 }
 
 /// The entire current state of a single game of Rummikub
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameState {
     pub table: PublicGameState,
     pub players: VecDeque<Player>,
+    /// The seed this game was started with, the same seed will always produce the same game
+    pub seed: u64,
+    /// Seeded at the start of the game, any random choice made during the game must come from here
+    pub rng: GameRng,
+    /// Games saved before there was a turn limit have none
+    #[serde(default)]
+    pub timer: TurnTimer,
 }
 
 impl GameState {
     /// Initializes game loop based on provided configuration
     pub fn init_game(conf: GameConfig) -> GameState {
-        // TODO handle some kind of random seed to make things reproducible
+        let seed = conf.seed.unwrap_or_else(|| rand::thread_rng().gen());
+        let mut rng = GameRng::seed_from_u64(seed);
         let mut board = PublicGameState {
-            boneyard: Boneyard::new_game(),
+            boneyard: Boneyard::new_game_from(&mut rng),
             face_up: FaceUpTiles::new(),
         };
 
@@ -40,11 +52,74 @@ impl GameState {
             let (rack, new_bones) = Rack::draw_initial_tiles(&board.boneyard);
             board.boneyard = new_bones;
             let info = PlayerInfo::of(&i.to_string());
-            players.push_back(Player { rack, info });
+            let strategy = match conf.strategies.len() {
+                0 => default_strategy(),
+                len => conf.strategies[(i as usize - 1) % len].clone(),
+            };
+            players.push_back(Player {
+                rack,
+                info,
+                strategy,
+            });
         }
         GameState {
             table: board,
             players,
+            seed,
+            rng,
+            timer: TurnTimer {
+                limit: conf.turn_limit,
+                clock: conf.clock,
+            },
         }
     }
 }
+
+#[cfg(test)]
+mod seeded_game_tests {
+    use super::*;
+    use crate::game_loop::main_game_loop;
+
+    fn seeded(seed: u64) -> GameConfig {
+        GameConfig {
+            num_players: 4,
+            seed: Some(seed),
+            ..GameConfig::default()
+        }
+    }
+
+    #[test]
+    fn same_seed_same_deal() {
+        let first = GameState::init_game(seeded(1234));
+        let second = GameState::init_game(seeded(1234));
+        assert_eq!(first.seed, 1234);
+        assert_eq!(first.table.boneyard, second.table.boneyard);
+        assert_eq!(first.players, second.players);
+
+        let other = GameState::init_game(seeded(4321));
+        assert_ne!(first.table.boneyard, other.table.boneyard);
+    }
+
+    #[test]
+    fn unseeded_game_records_its_seed() {
+        let random = GameState::init_game(GameConfig {
+            num_players: 2,
+            ..GameConfig::default()
+        });
+        let replayed = GameState::init_game(GameConfig {
+            num_players: 2,
+            seed: Some(random.seed),
+            ..GameConfig::default()
+        });
+        assert_eq!(random.table.boneyard, replayed.table.boneyard);
+        assert_eq!(random.players, replayed.players);
+    }
+
+    #[test]
+    fn same_seed_same_game() {
+        let first = main_game_loop(GameState::init_game(seeded(99)));
+        let second = main_game_loop(GameState::init_game(seeded(99)));
+        assert_eq!(first.winner, second.winner);
+        assert_eq!(first.loser, second.loser);
+    }
+}
@@ -0,0 +1,319 @@
+use crate::domain::player::rack::Rack;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::table::manipulation::{
+    human_like_algorithm, place_new_tiles_simple, retrieve_and_replay_joker,
+    shatter_and_recombobulate,
+};
+use crate::domain::table::solver::{solve, PlacementGoal};
+use crate::game_loop::observation::Observation;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fmt::Formatter;
+use std::sync::Arc;
+
+/// A way of playing Rummikub. Given only what the player can observe, their own rack and what
+/// everyone can see, a strategy proposes what the rack and the face up tiles should look like at the end of
+/// the turn. Each player carries their own, so different strategies can be pitted against
+/// each other in the same game.
+pub trait Strategy: Send + Sync {
+    /// Short name of the strategy, used to tell players apart in logs and results
+    fn name(&self) -> &str;
+
+    /// Once the initial meld has been played, attempts to place tiles from the rack by
+    /// manipulating the face up tiles. Returns None if no change could be made.
+    fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)>;
+
+    /// Proposes the entire turn, returning the new rack and face up tiles, along with the steps taken.
+    /// None indicates nothing could be placed, and therefore the player must draw.
+    /// By default: plays the initial meld if possible, otherwise places any complete sets from
+    /// the rack, rearranges the table, and finally tries to swap a rack tile in for a joker. New bots may replace this entirely.
+    /// Whatever is proposed will be validated before it is accepted.
+    fn propose_turn(
+        &self,
+        observation: &Observation,
+    ) -> Option<(Rack, FaceUpTiles, Vec<MovePath>)> {
+        let mut mut_rack = observation.rack.clone();
+        let mut face_up = observation.face_up.clone();
+        let mut path = vec![];
+
+        // The table may only be manipulated on the turns after the initial meld
+        if !mut_rack.played_initial_meld {
+            if let Some(meld) = mut_rack.can_play_initial_meld() {
+                info!("Playing Initial Meld!");
+                mut_rack = mut_rack.remove_meld(&meld).ok()?;
+                face_up = face_up.place_new_sets(&meld.sets);
+                path.push(MovePath::InitialMeld);
+                info!("Table Now Has:\n{}", face_up)
+            }
+        } else {
+            if let Some((complete_sets, rack_without_sets)) = mut_rack.sets_on_rack() {
+                info!("Placing Complete Sets from Rack!");
+                face_up = face_up.place_new_sets(&complete_sets);
+                mut_rack = rack_without_sets;
+                path.push(MovePath::SetsFromRack);
+                info!("Table Now Has:\n{}", face_up)
+            }
+
+            if let Some((rack_after_placing, new_face_up)) = self.rearrange(&mut_rack, &face_up) {
+                info!("Rearranged Face Up Tiles and Placing some from Rack!");
+                face_up = new_face_up;
+                mut_rack = rack_after_placing;
+                path.push(MovePath::Rearranged(self.name().to_string()));
+                info!("Table Now Has:\n{}", face_up)
+            }
+
+            if let Some((rack_after_swap, new_face_up)) =
+                retrieve_and_replay_joker(&mut_rack, &face_up)
+            {
+                info!("Retrieved a Joker and Played it Elsewhere!");
+                face_up = new_face_up;
+                mut_rack = rack_after_swap;
+                path.push(MovePath::JokerRetrieval);
+                info!("Table Now Has:\n{}", face_up)
+            }
+        }
+
+        if path.is_empty() {
+            None
+        } else {
+            Some((mut_rack, face_up, path))
+        }
+    }
+}
+
+/// One of the steps a strategy took to arrive at the turn it proposed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MovePath {
+    /// Sets from the rack worth at least 30 points, see Rack::can_play_initial_meld
+    InitialMeld,
+    /// Complete sets already on the rack, see Rack::sets_on_rack
+    SetsFromRack,
+    /// The table was rearranged by the manipulation of the named strategy
+    Rearranged(String),
+    /// A rack tile was swapped in for a joker, see retrieve_and_replay_joker
+    JokerRetrieval,
+    /// A person made these moves themselves, see TurnInProgress
+    ByHand,
+    /// Proposed as a whole table by a client connected to the server
+    Remote,
+}
+
+impl fmt::Display for MovePath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MovePath::InitialMeld => write!(f, "initial meld"),
+            MovePath::SetsFromRack => write!(f, "sets from rack"),
+            MovePath::Rearranged(name) => write!(f, "rearranged by {}", name),
+            MovePath::JokerRetrieval => write!(f, "joker retrieval"),
+            MovePath::ByHand => write!(f, "by hand"),
+            MovePath::Remote => write!(f, "remote client"),
+        }
+    }
+}
+
+/// Strategies are printed by name only, as they usually carry no state
+impl fmt::Debug for dyn Strategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Strategy({})", self.name())
+    }
+}
+
+/// Tries each tile of the rack against each run and then each group, as a person might
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HumanLike;
+
+impl Strategy for HumanLike {
+    fn name(&self) -> &str {
+        "human_like"
+    }
+
+    fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+        human_like_algorithm(rack, face_up)
+    }
+}
+
+/// Adds tiles from the rack to the first set on the table that will accept them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlaceNewTilesSimple;
+
+impl Strategy for PlaceNewTilesSimple {
+    fn name(&self) -> &str {
+        "place_new_tiles_simple"
+    }
+
+    fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+        place_new_tiles_simple(rack, face_up)
+    }
+}
+
+/// Breaks the whole table apart, and rebuilds it including the tiles on the rack
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShatterAndRecombobulate;
+
+impl Strategy for ShatterAndRecombobulate {
+    fn name(&self) -> &str {
+        "shatter_and_recombobulate"
+    }
+
+    fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+        let (remaining, new_face_up) = shatter_and_recombobulate(rack, face_up)?;
+        let new_rack = Rack::new(&remaining.0, Some(rack.played_initial_meld)).ok()?;
+        Some((new_rack, new_face_up))
+    }
+}
+
+/// Finds the arrangement of the whole table plus the rack that places the most tiles, exactly
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptimalPlacement {
+    pub goal: PlacementGoal,
+}
+
+impl Strategy for OptimalPlacement {
+    fn name(&self) -> &str {
+        match self.goal {
+            PlacementGoal::MostTiles => "optimal_placement",
+            PlacementGoal::MostPoints => "optimal_placement_points",
+        }
+    }
+
+    fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+        solve(rack, face_up, self.goal)
+    }
+}
+
+/// The strategy used for any player which was not assigned one
+pub fn default_strategy() -> Arc<dyn Strategy> {
+    Arc::new(HumanLike)
+}
+
+/// Every built-in strategy, useful for pitting them against each other
+pub fn all_strategies() -> Vec<Arc<dyn Strategy>> {
+    vec![
+        Arc::new(HumanLike),
+        Arc::new(PlaceNewTilesSimple),
+        Arc::new(ShatterAndRecombobulate),
+        Arc::new(OptimalPlacement {
+            goal: PlacementGoal::MostTiles,
+        }),
+        Arc::new(OptimalPlacement {
+            goal: PlacementGoal::MostPoints,
+        }),
+    ]
+}
+
+/// Finds a built-in strategy by its name
+pub fn strategy_by_name(name: &str) -> Option<Arc<dyn Strategy>> {
+    all_strategies().into_iter().find(|s| s.name() == name)
+}
+
+/// Saves a strategy as just its name, use with #[serde(with = "by_name")].
+/// Only built-in strategies can be loaded again
+pub mod by_name {
+    use super::{strategy_by_name, Strategy};
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::sync::Arc;
+
+    pub fn serialize<S: Serializer>(
+        strategy: &Arc<dyn Strategy>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(strategy.name())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Arc<dyn Strategy>, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        strategy_by_name(&name).ok_or_else(|| D::Error::custom(format!("no strategy {}", name)))
+    }
+}
+
+#[cfg(test)]
+mod strategy_tests {
+    use super::*;
+    use crate::domain::sets::run::Run;
+    use crate::domain::sets::Set;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile::RegularTile;
+    use crate::game_loop::main_game_loop;
+    use crate::game_loop::meta::GameConfig;
+    use crate::game_loop::state::GameState;
+
+    #[test]
+    fn lookup_by_name() {
+        for strategy in all_strategies() {
+            let found = strategy_by_name(strategy.name()).expect("Built-in must be found");
+            assert_eq!(found.name(), strategy.name());
+        }
+        assert!(strategy_by_name("no_such_bot").is_none());
+    }
+
+    #[test]
+    fn default_turn_rearranges_after_initial_meld() {
+        let rack = Rack::new(&vec![RegularTile(Blue, Three)], Some(true)).unwrap();
+        let face_up = FaceUpTiles {
+            sets: vec![Set::Run(Run::of(Four, Blue, 3).unwrap())],
+        };
+        for strategy in all_strategies() {
+            let (new_rack, new_face_up, path) = strategy
+                .propose_turn(&Observation::new(&rack, &face_up))
+                .expect("Blue three fits on the run");
+            assert_eq!(
+                path,
+                vec![MovePath::Rearranged(strategy.name().to_string())]
+            );
+            assert!(new_rack.is_empty(), "{:?}", strategy);
+            assert_eq!(
+                new_face_up.sets,
+                vec![Set::Run(Run::of(Three, Blue, 4).unwrap())]
+            );
+        }
+    }
+
+    #[test]
+    fn nothing_to_play_proposes_nothing() {
+        let rack = Rack::new(&vec![RegularTile(Blue, Three)], Some(false)).unwrap();
+        let seen = Observation::new(&rack, &FaceUpTiles::new());
+        for strategy in all_strategies() {
+            assert!(strategy.propose_turn(&seen).is_none());
+        }
+    }
+
+    #[test]
+    fn mixed_strategies_in_one_game() {
+        let conf = GameConfig {
+            num_players: 3,
+            seed: Some(3),
+            strategies: all_strategies(),
+            ..GameConfig::default()
+        };
+        let game = GameState::init_game(conf);
+        let names: Vec<&str> = game.players.iter().map(|p| p.strategy.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "human_like",
+                "place_new_tiles_simple",
+                "shatter_and_recombobulate"
+            ]
+        );
+        main_game_loop(game);
+    }
+
+    #[test]
+    fn optimal_placement_plays_a_whole_game() {
+        let conf = GameConfig {
+            num_players: 2,
+            seed: Some(6),
+            strategies: vec![
+                strategy_by_name("optimal_placement").unwrap(),
+                strategy_by_name("optimal_placement_points").unwrap(),
+            ],
+            ..GameConfig::default()
+        };
+        main_game_loop(GameState::init_game(conf));
+    }
+}
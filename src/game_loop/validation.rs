@@ -0,0 +1,317 @@
+use crate::domain::notation;
+use crate::domain::player::initial_meld::MINIMUM_MELD_SCORE;
+use crate::domain::player::rack::Rack;
+use crate::domain::score_value::ScoreValue;
+use crate::domain::score_value::ScoringRule::OnTable;
+use crate::domain::sets::Set;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::tiles::tile_sequence::{TileSequence, TileSequenceType};
+use crate::domain::tiles::Tile;
+use crate::domain::Decompose;
+use crate::game_loop::state::PublicGameState;
+use std::fmt;
+use std::fmt::Formatter;
+
+/// The reasons a proposed turn can be rejected, as per the official rules
+#[derive(Debug, Clone, PartialEq)]
+pub enum TurnViolation {
+    /// Nothing was placed from the rack, therefore the player must draw instead
+    NoTilesPlaced,
+    /// The set at this position on the proposed table is neither a valid Run nor a valid Group
+    InvalidSet(usize),
+    /// A tile was taken from the table, and did not end up back on the table
+    TileTakenFromTable(Tile),
+    /// A joker which was freed this turn was not played again this turn
+    JokerNotPlayed,
+    /// A tile appeared which was on neither the rack nor the table at the start of the turn
+    UnknownTile(Tile),
+    /// The rack does not match the rack at the start of the turn, minus the tiles placed
+    RackMismatch,
+    /// Tiles can only be drawn by the game, never as part of a proposed turn
+    BoneyardChanged,
+    /// The initial meld must be worth at least 30 points, this one was only worth so much
+    InitialMeldTooLow(ScoreValue),
+    /// The initial meld must use tiles from the rack only, and leave the table untouched
+    InitialMeldUsedTable,
+}
+
+/// Tiles are written in the plain notation with {:#}
+impl fmt::Display for TurnViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TurnViolation::NoTilesPlaced => write!(f, "No tiles were placed from the rack"),
+            TurnViolation::InvalidSet(i) => write!(f, "Set #{} is not a valid run or group", i),
+            TurnViolation::TileTakenFromTable(t) => {
+                notation::write_tile_in_text(f, t)?;
+                write!(f, "was taken from the table, and not played")
+            }
+            TurnViolation::JokerNotPlayed => write!(f, "A freed joker must be played this turn"),
+            TurnViolation::UnknownTile(t) => {
+                notation::write_tile_in_text(f, t)?;
+                write!(f, "did not come from the rack")
+            }
+            TurnViolation::RackMismatch => write!(f, "Rack does not match the tiles placed"),
+            TurnViolation::BoneyardChanged => write!(f, "The boneyard cannot be changed"),
+            TurnViolation::InitialMeldTooLow(score) => {
+                write!(f, "Initial meld is only {} pts, needs 30", score)
+            }
+            TurnViolation::InitialMeldUsedTable => {
+                write!(f, "Initial meld may only use tiles from the rack")
+            }
+        }
+    }
+}
+
+/// The authoritative check of a single turn. Compares the state at the start of the turn, with
+/// the proposed state at the end of it, and accepts the turn only if it follows all the rules.
+/// Every proposed turn, whether from a strategy or from a person, must pass through here.
+pub fn validate_turn(
+    prev_rack: &Rack,
+    prev_table: &PublicGameState,
+    rack: &Rack,
+    table: &PublicGameState,
+) -> Result<(), TurnViolation> {
+    if table.boneyard != prev_table.boneyard {
+        return Err(TurnViolation::BoneyardChanged);
+    }
+    validate_placement(prev_rack, &prev_table.face_up, rack, &table.face_up)
+}
+
+/// Everything validate_turn checks, apart from the boneyard. Which is all that can be checked
+/// with only what a player observes, such as before a turn is proposed
+pub fn validate_placement(
+    prev_rack: &Rack,
+    prev_face_up: &FaceUpTiles,
+    rack: &Rack,
+    face_up: &FaceUpTiles,
+) -> Result<(), TurnViolation> {
+    if let Some(i) = face_up.sets.iter().position(|s| !s.is_valid()) {
+        return Err(TurnViolation::InvalidSet(i));
+    }
+
+    let prev_on_table = TileSequenceType::of(prev_face_up);
+    let on_table = TileSequenceType::of(face_up);
+
+    // Everything that was on the table must still be on the table
+    let taken_from_table = prev_on_table.difference(&on_table);
+    if taken_from_table.iter().any(|t| t.is_joker()) {
+        return Err(TurnViolation::JokerNotPlayed);
+    }
+    if let Some(tile) = taken_from_table.first() {
+        return Err(TurnViolation::TileTakenFromTable(*tile));
+    }
+
+    // Anything new on the table must have come from the rack
+    let placed: TileSequence = on_table.difference(&prev_on_table);
+    let remaining = TileSequenceType::of(prev_rack);
+    if let Some(tile) = placed
+        .iter()
+        .find(|&t| remaining.remove(&vec![*t]).is_none())
+    {
+        return Err(TurnViolation::UnknownTile(*tile));
+    }
+    let mut expected_rack = remaining.remove(&placed).ok_or(TurnViolation::RackMismatch)?.0;
+    let mut actual_rack = rack.decompose();
+    expected_rack.sort();
+    actual_rack.sort();
+    if expected_rack != actual_rack {
+        return Err(TurnViolation::RackMismatch);
+    }
+
+    if placed.is_empty() {
+        return Err(TurnViolation::NoTilesPlaced);
+    }
+
+    if !prev_rack.played_initial_meld {
+        let new_sets = sets_added(&prev_face_up.sets, &face_up.sets)
+            .ok_or(TurnViolation::InitialMeldUsedTable)?;
+        let mut meld_score = ScoreValue::of_u16(0);
+        for set in new_sets {
+            meld_score += set.score(OnTable).map_err(|_| TurnViolation::RackMismatch)?;
+        }
+        if meld_score < MINIMUM_MELD_SCORE {
+            return Err(TurnViolation::InitialMeldTooLow(meld_score));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the sets which were added, provided that every one of the original sets
+/// is still present and unchanged. Otherwise returns None
+fn sets_added<'a>(original: &[Set], proposed: &'a [Set]) -> Option<Vec<&'a Set>> {
+    let mut added: Vec<&Set> = proposed.iter().collect();
+    for set in original {
+        let pos = added.iter().position(|&s| s == set)?;
+        added.remove(pos);
+    }
+    Some(added)
+}
+
+#[cfg(test)]
+mod validation_tests {
+    use super::*;
+    use crate::domain::sets::group::Group;
+    use crate::domain::sets::run::Run;
+    use crate::domain::table::boneyard::Boneyard;
+    use crate::domain::table::face_up::FaceUpTiles;
+    use crate::domain::tiles::color::Color::*;
+    use crate::domain::tiles::number::Number::*;
+    use crate::domain::tiles::Tile::{JokersWild, RegularTile};
+
+    fn table_of(sets: Vec<Set>) -> PublicGameState {
+        PublicGameState {
+            boneyard: Boneyard {
+                bones: vec![RegularTile(Red, One)],
+            },
+            face_up: FaceUpTiles { sets },
+        }
+    }
+
+    fn rack_of(tiles: Vec<Tile>, played_initial_meld: bool) -> Rack {
+        Rack::new(&tiles, Some(played_initial_meld)).unwrap()
+    }
+
+    #[test]
+    fn accepts_tile_added_to_run() {
+        let prev_rack = rack_of(vec![RegularTile(Blue, Three), RegularTile(Red, Nine)], true);
+        let prev_table = table_of(vec![Set::Run(Run::of(Four, Blue, 3).unwrap())]);
+        let rack = rack_of(vec![RegularTile(Red, Nine)], true);
+        let table = table_of(vec![Set::Run(Run::of(Three, Blue, 4).unwrap())]);
+        assert_eq!(Ok(()), validate_turn(&prev_rack, &prev_table, &rack, &table));
+    }
+
+    #[test]
+    fn rejects_doing_nothing() {
+        let rack = rack_of(vec![RegularTile(Blue, Three)], true);
+        let table = table_of(vec![Set::Run(Run::of(Four, Blue, 3).unwrap())]);
+        assert_eq!(
+            Err(TurnViolation::NoTilesPlaced),
+            validate_turn(&rack, &table, &rack, &table)
+        );
+    }
+
+    #[test]
+    fn rejects_changing_the_boneyard() {
+        let prev_rack = rack_of(vec![RegularTile(Blue, Three)], true);
+        let prev_table = table_of(vec![Set::Run(Run::of(Four, Blue, 3).unwrap())]);
+        let rack = rack_of(vec![], true);
+        let mut table = table_of(vec![Set::Run(Run::of(Three, Blue, 4).unwrap())]);
+        table.boneyard = Boneyard { bones: vec![] };
+        assert_eq!(
+            Err(TurnViolation::BoneyardChanged),
+            validate_turn(&prev_rack, &prev_table, &rack, &table)
+        );
+    }
+
+    #[test]
+    fn rejects_taking_tile_back_to_rack() {
+        let prev_rack = rack_of(vec![RegularTile(Red, Nine)], true);
+        let prev_table = table_of(vec![Set::Run(Run::of(Four, Blue, 4).unwrap())]);
+        let rack = rack_of(vec![RegularTile(Red, Nine), RegularTile(Blue, Seven)], true);
+        let table = table_of(vec![Set::Run(Run::of(Four, Blue, 3).unwrap())]);
+        assert_eq!(
+            Err(TurnViolation::TileTakenFromTable(RegularTile(Blue, Seven))),
+            validate_turn(&prev_rack, &prev_table, &rack, &table)
+        );
+    }
+
+    #[test]
+    fn rejects_freed_joker_left_on_rack() {
+        let with_joker = Run::parse(&[
+            RegularTile(Blue, Four),
+            JokersWild,
+            RegularTile(Blue, Six),
+        ])
+        .unwrap();
+        let prev_rack = rack_of(vec![RegularTile(Blue, Five)], true);
+        let prev_table = table_of(vec![Set::Run(with_joker)]);
+        let rack = rack_of(vec![JokersWild], true);
+        let table = table_of(vec![Set::Run(Run::of(Four, Blue, 3).unwrap())]);
+        assert_eq!(
+            Err(TurnViolation::JokerNotPlayed),
+            validate_turn(&prev_rack, &prev_table, &rack, &table)
+        );
+    }
+
+    #[test]
+    fn rejects_tiles_from_nowhere() {
+        let prev_rack = rack_of(vec![RegularTile(Red, Nine)], true);
+        let prev_table = table_of(vec![Set::Run(Run::of(Four, Blue, 3).unwrap())]);
+        let table = table_of(vec![Set::Run(Run::of(Three, Blue, 4).unwrap())]);
+        assert_eq!(
+            Err(TurnViolation::UnknownTile(RegularTile(Blue, Three))),
+            validate_turn(&prev_rack, &prev_table, &prev_rack, &table)
+        );
+
+        // Placed the right tile, but the rack kept it as well
+        let prev_rack = rack_of(vec![RegularTile(Blue, Three)], true);
+        assert_eq!(
+            Err(TurnViolation::RackMismatch),
+            validate_turn(&prev_rack, &prev_table, &prev_rack, &table)
+        );
+    }
+
+    #[test]
+    fn accepts_initial_meld_of_thirty() {
+        let prev_rack = rack_of(Run::of(Nine, Red, 3).unwrap().decompose(), false);
+        let prev_table = table_of(vec![Set::Run(Run::of(Four, Blue, 3).unwrap())]);
+        let rack = rack_of(vec![], false);
+        let table = table_of(vec![
+            Set::Run(Run::of(Four, Blue, 3).unwrap()),
+            Set::Run(Run::of(Nine, Red, 3).unwrap()),
+        ]);
+        assert_eq!(Ok(()), validate_turn(&prev_rack, &prev_table, &rack, &table));
+    }
+
+    #[test]
+    fn rejects_initial_meld_under_thirty() {
+        let prev_rack = rack_of(Run::of(One, Red, 3).unwrap().decompose(), false);
+        let prev_table = table_of(vec![]);
+        let rack = rack_of(vec![], false);
+        let table = table_of(vec![Set::Run(Run::of(One, Red, 3).unwrap())]);
+        assert_eq!(
+            Err(TurnViolation::InitialMeldTooLow(ScoreValue::of_u16(6))),
+            validate_turn(&prev_rack, &prev_table, &rack, &table)
+        );
+    }
+
+    #[test]
+    fn initial_meld_scores_joker_as_the_tile_it_represents() {
+        let with_joker = Run::parse(&[
+            RegularTile(Red, Nine),
+            JokersWild,
+            RegularTile(Red, Eleven),
+        ])
+        .unwrap();
+        let prev_rack = rack_of(with_joker.decompose(), false);
+        let prev_table = table_of(vec![]);
+        let rack = rack_of(vec![], false);
+        let table = table_of(vec![Set::Run(with_joker)]);
+        assert_eq!(Ok(()), validate_turn(&prev_rack, &prev_table, &rack, &table));
+    }
+
+    #[test]
+    fn rejects_initial_meld_using_table() {
+        let prev_rack = rack_of(
+            vec![
+                RegularTile(Red, Ten),
+                RegularTile(Blue, Ten),
+                RegularTile(Black, Ten),
+                RegularTile(Orange, Four),
+            ],
+            false,
+        );
+        let prev_table = table_of(vec![Set::Group(
+            Group::of(Four, &vec![Red, Blue, Black]).unwrap(),
+        )]);
+        let rack = rack_of(vec![], false);
+        let table = table_of(vec![
+            Set::Group(Group::of(Four, &vec![Red, Blue, Black, Orange]).unwrap()),
+            Set::Group(Group::of(Ten, &vec![Red, Blue, Black]).unwrap()),
+        ]);
+        assert_eq!(
+            Err(TurnViolation::InitialMeldUsedTable),
+            validate_turn(&prev_rack, &prev_table, &rack, &table)
+        );
+    }
+}
@@ -0,0 +1,26 @@
+#![allow(dead_code, unused_imports, unused_variables)] //TODO remove during clean up phase
+
+//! Rustikub, a simulation of the tabletop game Rummikub.
+//!
+//! The domain module holds the tiles, sets, table and players, while the game loop drives
+//! a full game from a GameState until there is a GameOutcome.
+
+// TODO add logging crate (Tracing), and replace print lines
+
+pub mod domain;
+pub mod game_loop;
+pub mod persistence;
+pub mod server;
+
+pub use game_loop::events::{replay, EventSink, GameEvent};
+pub use game_loop::main_game_loop;
+pub use game_loop::main_game_loop_with_events;
+pub use game_loop::match_play::{play_match, MatchConfig, MatchLength, MatchSummary};
+pub use game_loop::meta::{GameConfig, GameOutcome};
+pub use game_loop::observation::Observation;
+pub use game_loop::save::LoadError;
+pub use game_loop::simulation::{simulate, SimulationConfig, SimulationReport};
+pub use game_loop::state::{GameState, PublicGameState};
+pub use persistence::{GameId, GameRepository, GameSummary, RepositoryError};
+pub use server::web::serve_web;
+pub use server::{host_game, ServerConfig};
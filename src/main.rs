@@ -1,39 +1,112 @@
-#![allow(dead_code, unused_imports, unused_variables)] //TODO remove during clean up phase
+mod cli;
 
-// TODO add logging crate (Tracing), and replace print lines
-
-extern crate core;
-
-use crate::game_loop::meta::GameConfig;
-
-mod domain;
-mod game_loop;
-mod persistence;
+use log::LevelFilter;
+use rustikub::{
+    host_game, main_game_loop_with_events, serve_web, GameConfig, GameState, ServerConfig,
+};
 
 fn main() {
-    use game_loop::state::GameState;
-    use game_loop::*;
     println!("Hello There! Welcome to Rustikub!");
-    println!("Now Playing A Game With 4 Players");
-    let conf = GameConfig { num_players: 4 };
-    let game1 = GameState::init_game(conf);
-    let result = main_game_loop(game1);
-    println!("Game Complete! Result: {}", result);
-}
-
-#[cfg(test)]
-mod end_to_end_run_game_for_debugging {
-    use super::*;
-    use crate::game_loop::main_game_loop;
-    use crate::game_loop::state::GameState;
-    use std::fmt::Display;
-
-    #[test]
-    fn run_game() {
-        let conf = GameConfig { num_players: 4 };
-        let game1 = GameState::init_game(conf);
-        let result = main_game_loop(game1);
-        let huh = format!("Test {}", result);
-        println!("{huh}");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("replay") => {
+            // Steps through a recorded game: replay [file], or replay <seed> [number of players]
+            // to play a game of the default strategies again
+            let arg = args.get(1).map_or(cli::replay::LAST_GAME, String::as_str);
+            match arg.parse() {
+                Ok(seed) => {
+                    let num_players = args.get(2).and_then(|arg| arg.parse().ok()).unwrap_or(4);
+                    cli::replay::replay_seed(seed, num_players);
+                }
+                Err(_) => cli::replay::replay_file(arg),
+            }
+        }
+        Some("tui") => {
+            // The same as play, but full screen: tui [number of bots] [seed]
+            let bots = args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(3);
+            let seed = args.get(2).and_then(|arg| arg.parse().ok());
+            cli::narrator::narrate(LevelFilter::Info);
+            cli::tui::play_tui(bots, seed);
+        }
+        Some("serve") => {
+            // Hosts a game over TCP: serve <port> [remote players] [bots] [seed]
+            let Some(port) = args.get(1).and_then(|arg| arg.parse::<u16>().ok()) else {
+                println!("Usage: rustikub serve <port> [players] [bots] [seed]");
+                return;
+            };
+            let conf = ServerConfig {
+                players: args.get(2).and_then(|arg| arg.parse().ok()).unwrap_or(2),
+                bots: args.get(3).and_then(|arg| arg.parse().ok()).unwrap_or(0),
+                seed: args.get(4).and_then(|arg| arg.parse().ok()),
+                ..ServerConfig::default()
+            };
+            let runtime = tokio::runtime::Runtime::new().expect("Could not start the server");
+            let result = runtime.block_on(async {
+                let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+                println!("Waiting for {} players on port {}", conf.players, port);
+                host_game(listener, conf).await
+            });
+            match result {
+                Ok(outcome) => println!("Game Complete! Result: {}", outcome),
+                Err(e) => println!("Server stopped: {}", e),
+            }
+        }
+        Some("web") => {
+            // Serves lobbies and games to browsers: web <port>
+            let Some(port) = args.get(1).and_then(|arg| arg.parse::<u16>().ok()) else {
+                println!("Usage: rustikub web <port>");
+                return;
+            };
+            let runtime = tokio::runtime::Runtime::new().expect("Could not start the server");
+            let result = runtime.block_on(async {
+                let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+                println!("Serving lobbies on port {}", port);
+                serve_web(listener).await
+            });
+            if let Err(e) = result {
+                println!("Server stopped: {}", e);
+            }
+        }
+        Some("db") => {
+            // Manages the rustikub schema in DATABASE_URL: db <migrate|status>
+            #[cfg(feature = "postgres")]
+            cli::db::db(args.get(1).map(String::as_str));
+            #[cfg(not(feature = "postgres"))]
+            println!("Built without the postgres feature");
+        }
+        Some("simulate") => {
+            // Plays many games at once and reports how every strategy did:
+            // simulate [games] [player counts, like 2,4] [strategies, like human_like,optimal_placement] [seed]
+            let games = args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(1000);
+            let player_counts = args.get(2).map_or(vec![4], |arg| {
+                arg.split(',').filter_map(|n| n.parse().ok()).collect()
+            });
+            let names: Vec<&str> = args.get(3).map_or(vec![], |arg| arg.split(',').collect());
+            let seed = args.get(4).and_then(|arg| arg.parse().ok());
+            cli::simulate::run_simulation(games, player_counts, &names, seed);
+        }
+        Some("play") => {
+            // Plays against bots from the keyboard: play [number of bots] [seed]
+            let bots = args.get(1).and_then(|arg| arg.parse().ok()).unwrap_or(3);
+            let seed = args.get(2).and_then(|arg| arg.parse().ok());
+            cli::narrator::narrate(LevelFilter::Info);
+            cli::play::play(bots, seed);
+        }
+        _ => {
+            println!("Now Playing A Game With 4 Players");
+            // Optionally pass a seed as the first argument to replay a previous game
+            let seed = args.first().and_then(|arg| arg.parse().ok());
+            let conf = GameConfig {
+                num_players: 4,
+                seed,
+                ..GameConfig::default()
+            };
+            let game1 = GameState::init_game(conf);
+            println!("Game Seed: {}", game1.seed);
+            // Only bots are playing, so every rack can be shown
+            cli::narrator::narrate(LevelFilter::Debug);
+            let result = main_game_loop_with_events(game1, &mut cli::replay::recorder());
+            println!("Game Complete! Result: {}", result);
+        }
     }
 }
@@ -0,0 +1,391 @@
+//! Games kept in a single append-only file, for when there is no database around.
+//!
+//! Each line is one JSON record: a game as it was dealt, a turn of a game, or how a game ended.
+//! Tiles are written in the notation of domain::notation, which keeps a turn to a few hundred
+//! bytes. Lines are only ever added to the end, so if a process dies halfway through writing one,
+//! everything before it can still be read. Only one process should write to a file at a time.
+
+use crate::domain::player::rack::Rack;
+use crate::domain::player::Player;
+use crate::domain::table::boneyard::Boneyard;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::tiles::Tile;
+use crate::game_loop::meta::GameOutcome;
+use crate::game_loop::state::{GameState, PublicGameState};
+use crate::persistence::{
+    restore, GameId, GameRepository, GameSummary, RepositoryError, SavedConfig, SavedOutcome,
+    SavedPlayer,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+#[derive(Serialize, Deserialize)]
+enum Record {
+    Game {
+        game: GameId,
+        seed: u64,
+        config: SavedConfig,
+        /// In the order the game started in
+        players: Vec<SavedPlayer>,
+    },
+    Turn {
+        game: GameId,
+        turn: SavedTurn,
+    },
+    Outcome {
+        game: GameId,
+        outcome: SavedOutcome,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedTurn {
+    /// Who is up, counting seats from 0 in the order the game started in
+    up: usize,
+    /// In draw order, as a list of tiles
+    boneyard: String,
+    /// Every rack and whether they have played their initial meld, in seating order
+    racks: Vec<(String, bool)>,
+    table: String,
+}
+
+/// Where each game is in the file
+struct Indexed {
+    summary: GameSummary,
+    players: Vec<SavedPlayer>,
+    /// Where each turn starts, in bytes from the start of the file
+    turns: Vec<u64>,
+}
+
+struct Store {
+    file: File,
+    /// How far into the file the next record goes
+    end: u64,
+    games: BTreeMap<GameId, Indexed>,
+}
+
+pub struct FileRepository {
+    path: PathBuf,
+    store: Mutex<Store>,
+}
+
+impl FileRepository {
+    /// Opens the file, or starts a new one. Every record is read once, to find where each game is
+    pub fn open(path: &Path) -> Result<FileRepository, RepositoryError> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .truncate(false)
+            .open(path)?;
+        let mut games: BTreeMap<GameId, Indexed> = BTreeMap::new();
+        let mut end = 0;
+        let mut reader = BufReader::new(&mut file);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line)?;
+            if read == 0 || !line.ends_with('\n') {
+                break; // A line cut short was never finished, and is written over
+            }
+            index(&mut games, serde_json::from_str(&line)?, end)?;
+            end += read as u64;
+        }
+        file.set_len(end)?;
+        Ok(FileRepository {
+            path: path.to_path_buf(),
+            store: Mutex::new(Store { file, end, games }),
+        })
+    }
+
+    fn store(&self) -> MutexGuard<'_, Store> {
+        self.store.lock().expect("Store is never poisoned")
+    }
+}
+
+impl Store {
+    fn append(&mut self, record: Record) -> Result<(), RepositoryError> {
+        let mut line = serde_json::to_string(&record)?;
+        line.push('\n');
+        let written = self
+            .file
+            .write_all(line.as_bytes())
+            .map_err(RepositoryError::from)
+            .and_then(|()| index(&mut self.games, record, self.end));
+        if let Err(e) = written {
+            // Whatever part of the line made it, or a line that does not fit, is dropped again
+            self.file.set_len(self.end)?;
+            return Err(e);
+        }
+        self.end += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Keeps track of where a record is, if it fits with what came before it
+fn index(
+    games: &mut BTreeMap<GameId, Indexed>,
+    record: Record,
+    at: u64,
+) -> Result<(), RepositoryError> {
+    match record {
+        Record::Game {
+            game,
+            seed,
+            config,
+            players,
+        } => {
+            let summary = GameSummary {
+                id: game,
+                seed,
+                config,
+                turns: 0,
+                outcome: None,
+            };
+            let turns = vec![];
+            games.insert(
+                game,
+                Indexed {
+                    summary,
+                    players,
+                    turns,
+                },
+            );
+        }
+        Record::Turn { game, .. } => {
+            let indexed = games
+                .get_mut(&game)
+                .ok_or(RepositoryError::NoSuchGame(game))?;
+            indexed.turns.push(at);
+            indexed.summary.turns += 1;
+        }
+        Record::Outcome { game, outcome } => {
+            let indexed = games
+                .get_mut(&game)
+                .ok_or(RepositoryError::NoSuchGame(game))?;
+            indexed.summary.outcome = Some(outcome);
+        }
+    }
+    Ok(())
+}
+
+fn write_tiles(tiles: &[Tile]) -> String {
+    tiles
+        .iter()
+        .map(|t| format!("{:#}", t))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn read_tiles(text: &str) -> Result<Vec<Tile>, RepositoryError> {
+    text.split_whitespace()
+        .map(|t| {
+            t.parse()
+                .map_err(|_| RepositoryError::Invalid(t.to_string()))
+        })
+        .collect()
+}
+
+impl SavedTurn {
+    fn of(state: &GameState, players: &[SavedPlayer]) -> Result<SavedTurn, RepositoryError> {
+        let seat_of = |player: &Player| {
+            players
+                .iter()
+                .position(|p| p.info == player.info)
+                .ok_or_else(|| RepositoryError::Invalid(format!("{} is not playing", player.info)))
+        };
+        let mut racks = vec![(String::new(), false); players.len()];
+        for player in &state.players {
+            racks[seat_of(player)?] = (
+                write_tiles(&player.rack.rack),
+                player.rack.played_initial_meld,
+            );
+        }
+        Ok(SavedTurn {
+            up: seat_of(&state.players[0])?,
+            boneyard: write_tiles(&state.table.boneyard.bones),
+            racks,
+            table: format!("{:#}", state.table.face_up),
+        })
+    }
+}
+
+impl GameRepository for FileRepository {
+    async fn save_game(&self, start: &GameState) -> Result<GameId, RepositoryError> {
+        let players: Vec<SavedPlayer> = start.players.iter().map(SavedPlayer::of).collect();
+        let turn = SavedTurn::of(start, &players)?;
+        let mut store = self.store();
+        let game = store.games.keys().next_back().map_or(0, |last| last + 1);
+        store.append(Record::Game {
+            game,
+            seed: start.seed,
+            config: SavedConfig::of(start),
+            players,
+        })?;
+        store.append(Record::Turn { game, turn })?;
+        Ok(game)
+    }
+
+    async fn append_turn(&self, game: GameId, state: &GameState) -> Result<(), RepositoryError> {
+        let players = match self.store().games.get(&game) {
+            Some(indexed) => indexed.players.clone(),
+            None => return Err(RepositoryError::NoSuchGame(game)),
+        };
+        let turn = SavedTurn::of(state, &players)?;
+        self.store().append(Record::Turn { game, turn })
+    }
+
+    async fn finish_game(
+        &self,
+        game: GameId,
+        outcome: &GameOutcome,
+    ) -> Result<(), RepositoryError> {
+        let outcome = SavedOutcome::from(outcome);
+        self.store().append(Record::Outcome { game, outcome })
+    }
+
+    async fn load_turn(&self, game: GameId, turn: u32) -> Result<GameState, RepositoryError> {
+        let (summary, players, at) = {
+            let store = self.store();
+            let indexed = store
+                .games
+                .get(&game)
+                .ok_or(RepositoryError::NoSuchGame(game))?;
+            let at = *indexed
+                .turns
+                .get(turn as usize)
+                .ok_or(RepositoryError::NoSuchTurn { game, turn })?;
+            (indexed.summary.clone(), indexed.players.clone(), at)
+        };
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(at))?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let Record::Turn { turn: saved, .. } = serde_json::from_str(&line)? else {
+            return Err(RepositoryError::Invalid(format!("no turn at byte {}", at)));
+        };
+
+        let mut seated = VecDeque::new();
+        for (player, (tiles, melded)) in players.into_iter().zip(&saved.racks) {
+            seated.push_back(Player {
+                info: player.info,
+                rack: Rack {
+                    rack: read_tiles(tiles)?,
+                    played_initial_meld: *melded,
+                },
+                strategy: player.strategy,
+            });
+        }
+        seated.rotate_left(saved.up);
+        let table = PublicGameState {
+            boneyard: Boneyard {
+                bones: read_tiles(&saved.boneyard)?,
+            },
+            face_up: saved
+                .table
+                .parse::<FaceUpTiles>()
+                .map_err(|e| RepositoryError::Invalid(e.to_string()))?,
+        };
+        Ok(restore(summary.seed, &summary.config, table, seated))
+    }
+
+    async fn list_games(&self) -> Result<Vec<GameSummary>, RepositoryError> {
+        let store = self.store();
+        Ok(store.games.values().map(|g| g.summary.clone()).collect())
+    }
+
+    async fn outcomes(&self) -> Result<Vec<(GameId, SavedOutcome)>, RepositoryError> {
+        let store = self.store();
+        Ok(store
+            .games
+            .iter()
+            .filter_map(|(id, g)| Some((*id, g.summary.outcome.clone()?)))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod file_tests {
+    use super::*;
+    use crate::persistence::repository_contract;
+
+    /// A file of its own for each test, gone afterwards
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> TempFile {
+            let file = format!("rustikub_{}_{}.jsonl", name, std::process::id());
+            let path = std::env::temp_dir().join(file);
+            let _ = std::fs::remove_file(&path);
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn keeps_every_turn() {
+        let file = TempFile::new("every_turn");
+        repository_contract::keeps_every_turn(&FileRepository::open(&file.0).unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn keeps_turns_as_they_are_played() {
+        let file = TempFile::new("as_played");
+        let repository = FileRepository::open(&file.0).unwrap();
+        repository_contract::keeps_turns_as_they_are_played(&repository).await;
+    }
+
+    #[tokio::test]
+    async fn keeps_a_game_saved_partway_through() {
+        let file = TempFile::new("partway");
+        let repository = FileRepository::open(&file.0).unwrap();
+        repository_contract::keeps_a_game_saved_partway_through(&repository).await;
+    }
+
+    #[tokio::test]
+    async fn refuses_events_from_another_game() {
+        let file = TempFile::new("another_game");
+        let repository = FileRepository::open(&file.0).unwrap();
+        repository_contract::refuses_events_from_another_game(&repository).await;
+        assert_eq!(std::fs::metadata(&file.0).unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn reopens_up_to_the_last_whole_line() {
+        let file = TempFile::new("reopens");
+        let start = GameState::init_game(crate::game_loop::meta::GameConfig {
+            num_players: 2,
+            seed: Some(7),
+            ..Default::default()
+        });
+        let game = {
+            let repository = FileRepository::open(&file.0).unwrap();
+            repository.save_game(&start).await.unwrap()
+        };
+        let mut half_written = OpenOptions::new().append(true).open(&file.0).unwrap();
+        half_written.write_all(b"{\"Turn\":{\"game\":").unwrap();
+
+        let repository = FileRepository::open(&file.0).unwrap();
+        let listed = repository.list_games().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].turns, 1);
+        let loaded = repository.load_game(game).await.unwrap();
+        assert_eq!(loaded.players, start.players);
+        assert_eq!(loaded.table.boneyard, start.table.boneyard);
+
+        // Carries on from where the whole lines ended
+        let next = repository.save_game(&start).await.unwrap();
+        assert_eq!(next, game + 1);
+        let reopened = FileRepository::open(&file.0).unwrap();
+        assert_eq!(reopened.list_games().await.unwrap().len(), 2);
+    }
+}
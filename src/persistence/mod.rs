@@ -1 +1,472 @@
+//! Keeping played games around, to look at or carry on from later.
+//!
+//! Every backend is a GameRepository, and sits behind a cargo feature of the same name:
+//! postgres for the rustikub schema, and file-store for an append-only file that needs no setup.
+//! A stored game is every turn of it, each one the game as it was at the start of that turn.
+//! Turn 0 is the game as it was dealt, and the last turn is how it ended.
+
+#[cfg(feature = "postgres")]
 pub mod dto_adapters;
+#[cfg(feature = "file-store")]
+pub mod file;
+#[cfg(feature = "postgres")]
+pub mod postgres;
+
+use crate::domain::player::info::PlayerInfo;
+use crate::domain::player::Player;
+use crate::domain::table::boneyard::Boneyard;
+use crate::game_loop::clock::{system_clock, TurnTimer};
+use crate::game_loop::events::{replay, GameEvent};
+use crate::game_loop::meta::{GameEnd, GameOutcome, GameRng};
+use crate::game_loop::state::{GameState, PublicGameState};
+use crate::game_loop::strategy::Strategy;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Formatter;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{fmt, io};
+
+pub type GameId = i64;
+
+/// Somewhere to keep games, see the module docs
+pub trait GameRepository: Send + Sync {
+    /// Keeps a new game, as it was dealt
+    fn save_game(
+        &self,
+        start: &GameState,
+    ) -> impl Future<Output = Result<GameId, RepositoryError>> + Send;
+
+    /// Keeps the game as it is at the start of its next turn
+    fn append_turn(
+        &self,
+        game: GameId,
+        state: &GameState,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// Keeps how the game ended
+    fn finish_game(
+        &self,
+        game: GameId,
+        outcome: &GameOutcome,
+    ) -> impl Future<Output = Result<(), RepositoryError>> + Send;
+
+    /// The game as it was at the start of a turn, counting from 0.
+    /// Players are in turn order, with whoever was up at the front
+    fn load_turn(
+        &self,
+        game: GameId,
+        turn: u32,
+    ) -> impl Future<Output = Result<GameState, RepositoryError>> + Send;
+
+    /// Every game kept so far, oldest first
+    fn list_games(&self) -> impl Future<Output = Result<Vec<GameSummary>, RepositoryError>> + Send;
+
+    /// How every finished game ended, oldest first
+    fn outcomes(
+        &self,
+    ) -> impl Future<Output = Result<Vec<(GameId, SavedOutcome)>, RepositoryError>> + Send;
+
+    /// The game as it was dealt
+    fn load_game(
+        &self,
+        game: GameId,
+    ) -> impl Future<Output = Result<GameState, RepositoryError>> + Send {
+        self.load_turn(game, 0)
+    }
+
+    /// Keeps a whole game that has already been played, from the state it started in
+    /// and everything that happened since
+    fn save_played_game(
+        &self,
+        start: &GameState,
+        events: &[GameEvent],
+        outcome: &GameOutcome,
+    ) -> impl Future<Output = Result<GameId, RepositoryError>> + Send {
+        async move {
+            let turns = replay(start, events).ok_or(RepositoryError::NotReplayable)?;
+            let game = self.save_game(start).await?;
+            for state in &turns {
+                self.append_turn(game, state).await?;
+            }
+            self.finish_game(game, outcome).await?;
+            Ok(game)
+        }
+    }
+}
+
+/// What is kept of the GameConfig, the strategies are kept with each player
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedConfig {
+    pub num_players: u8,
+    pub turn_limit: Option<Duration>,
+}
+
+impl SavedConfig {
+    pub fn of(state: &GameState) -> SavedConfig {
+        SavedConfig {
+            num_players: state.players.len() as u8,
+            turn_limit: state.timer.limit,
+        }
+    }
+}
+
+/// Everything about a player besides their rack
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct SavedPlayer {
+    pub info: PlayerInfo,
+    #[serde(with = "crate::game_loop::strategy::by_name")]
+    pub strategy: Arc<dyn Strategy>,
+}
+
+impl SavedPlayer {
+    pub fn of(player: &Player) -> SavedPlayer {
+        SavedPlayer {
+            info: player.info.clone(),
+            strategy: player.strategy.clone(),
+        }
+    }
+}
+
+/// The standings of a finished game, without each player's final rack
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedOutcome {
+    pub end: GameEnd,
+    pub turns: u32,
+    /// From first place to last
+    pub standings: Vec<SavedStanding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedStanding {
+    pub player: PlayerInfo,
+    pub rank: usize,
+    pub rack_score: u16,
+    pub delta: i32,
+}
+
+impl From<&GameOutcome> for SavedOutcome {
+    fn from(outcome: &GameOutcome) -> Self {
+        SavedOutcome {
+            end: outcome.end,
+            turns: outcome.turns,
+            standings: outcome
+                .standings
+                .iter()
+                .map(|s| SavedStanding {
+                    player: s.player.info.clone(),
+                    rank: s.rank,
+                    rack_score: s.rack_score.as_u16(),
+                    delta: s.delta,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A game as listed, without any of its turns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: GameId,
+    pub seed: u64,
+    pub config: SavedConfig,
+    /// How many turns are kept, counting the deal
+    pub turns: u32,
+    /// None until the game is finished
+    pub outcome: Option<SavedOutcome>,
+}
+
+/// The reasons a game could not be kept or loaded
+#[derive(Debug)]
+pub enum RepositoryError {
+    #[cfg(feature = "postgres")]
+    Database(sqlx::Error),
+    /// A row which breaks a constraint of the schema, or the rules
+    #[cfg(feature = "postgres")]
+    Row(dto_adapters::DtoError),
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// The events do not fit the game they were said to be from, see replay
+    NotReplayable,
+    NoSuchGame(GameId),
+    NoSuchTurn {
+        game: GameId,
+        turn: u32,
+    },
+    /// Something kept which is not part of a valid game
+    Invalid(String),
+}
+
+impl fmt::Display for RepositoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "postgres")]
+            RepositoryError::Database(e) => write!(f, "Database error: {}", e),
+            #[cfg(feature = "postgres")]
+            RepositoryError::Row(e) => write!(f, "Not a valid row: {}", e),
+            RepositoryError::Io(e) => write!(f, "Could not read or write the games: {}", e),
+            RepositoryError::Json(e) => write!(f, "Not a valid record: {}", e),
+            RepositoryError::NotReplayable => {
+                write!(f, "The events do not replay from the starting state")
+            }
+            RepositoryError::NoSuchGame(game) => write!(f, "There is no game {}", game),
+            RepositoryError::NoSuchTurn { game, turn } => {
+                write!(f, "Game {} has no turn {}", game, turn)
+            }
+            RepositoryError::Invalid(what) => write!(f, "Not a valid stored game: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for RepositoryError {}
+
+#[cfg(feature = "postgres")]
+impl From<sqlx::Error> for RepositoryError {
+    fn from(e: sqlx::Error) -> Self {
+        RepositoryError::Database(e)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<dto_adapters::DtoError> for RepositoryError {
+    fn from(e: dto_adapters::DtoError) -> Self {
+        RepositoryError::Row(e)
+    }
+}
+
+impl From<io::Error> for RepositoryError {
+    fn from(e: io::Error) -> Self {
+        RepositoryError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for RepositoryError {
+    fn from(e: serde_json::Error) -> Self {
+        RepositoryError::Json(e)
+    }
+}
+
+/// Puts a game back together from what was kept of it. Only the shuffle draws from the rng,
+/// so it carries on from right after the shuffle, and the turn limit is timed by the real clock
+pub(crate) fn restore(
+    seed: u64,
+    config: &SavedConfig,
+    table: PublicGameState,
+    players: VecDeque<Player>,
+) -> GameState {
+    let mut rng = GameRng::seed_from_u64(seed);
+    Boneyard::new_game_from(&mut rng);
+    GameState {
+        table,
+        players,
+        seed,
+        rng,
+        timer: TurnTimer {
+            limit: config.turn_limit,
+            clock: system_clock(),
+        },
+    }
+}
+
+/// What every backend is tested against
+#[cfg(test)]
+pub(crate) mod repository_contract {
+    use super::*;
+    use crate::domain::player::rack::Rack;
+    use crate::domain::table::face_up::FaceUpTiles;
+    use crate::domain::tiles::Tile;
+    use crate::domain::Decompose;
+    use crate::game_loop::main_game_loop_with_events;
+    use crate::game_loop::meta::GameConfig;
+
+    fn played(seed: u64) -> (GameState, Vec<GameEvent>, GameOutcome) {
+        let start = GameState::init_game(GameConfig {
+            num_players: 3,
+            seed: Some(seed),
+            turn_limit: Some(Duration::from_secs(90)),
+            ..GameConfig::default()
+        });
+        let mut events: Vec<GameEvent> = vec![];
+        let outcome = main_game_loop_with_events(start.clone(), &mut events);
+        (start, events, outcome)
+    }
+
+    /// Not every backend keeps the sets in the order they were placed
+    fn sorted(face_up: &FaceUpTiles) -> (usize, Vec<Tile>) {
+        let mut tiles = face_up.decompose();
+        tiles.sort();
+        (face_up.sets.len(), tiles)
+    }
+
+    fn assert_same(loaded: &GameState, expected: &GameState) {
+        assert_eq!(loaded.players, expected.players);
+        // Players only compare by the score of their rack, so the racks are compared as well
+        let racks = |state: &GameState| -> Vec<Rack> {
+            state.players.iter().map(|p| p.rack.clone()).collect()
+        };
+        assert_eq!(racks(loaded), racks(expected));
+        assert_eq!(loaded.table.boneyard, expected.table.boneyard);
+        assert_eq!(
+            sorted(&loaded.table.face_up),
+            sorted(&expected.table.face_up)
+        );
+    }
+
+    pub async fn keeps_every_turn(repository: &impl GameRepository) {
+        let (start, events, outcome) = played(11);
+        let game = repository
+            .save_played_game(&start, &events, &outcome)
+            .await
+            .unwrap();
+
+        let dealt = repository.load_game(game).await.unwrap();
+        assert_eq!(dealt.seed, 11);
+        assert_eq!(dealt.rng, start.rng);
+        assert_eq!(dealt.timer.limit, Some(Duration::from_secs(90)));
+        assert_same(&dealt, &start);
+
+        let turns = replay(&start, &events).unwrap();
+        for (i, expected) in turns.iter().enumerate() {
+            let loaded = repository.load_turn(game, i as u32 + 1).await.unwrap();
+            assert_same(&loaded, expected);
+        }
+        let past_the_end = turns.len() as u32 + 1;
+        assert!(matches!(
+            repository.load_turn(game, past_the_end).await,
+            Err(RepositoryError::NoSuchTurn { .. })
+        ));
+        assert!(matches!(
+            repository.load_turn(game + 1, 0).await,
+            Err(RepositoryError::NoSuchGame(_))
+        ));
+
+        let summary = GameSummary {
+            id: game,
+            seed: 11,
+            config: SavedConfig::of(&start),
+            turns: past_the_end,
+            outcome: Some(SavedOutcome::from(&outcome)),
+        };
+        assert_eq!(repository.list_games().await.unwrap(), vec![summary]);
+        assert_eq!(
+            repository.outcomes().await.unwrap(),
+            vec![(game, SavedOutcome::from(&outcome))]
+        );
+    }
+
+    pub async fn keeps_turns_as_they_are_played(repository: &impl GameRepository) {
+        let (start, events, outcome) = played(5);
+        let turns = replay(&start, &events).unwrap();
+        let game = repository.save_game(&start).await.unwrap();
+        repository.append_turn(game, &turns[0]).await.unwrap();
+        repository.append_turn(game, &turns[1]).await.unwrap();
+        assert_same(&repository.load_turn(game, 2).await.unwrap(), &turns[1]);
+
+        let listed = repository.list_games().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].turns, 3);
+        assert_eq!(listed[0].outcome, None);
+        assert!(repository.outcomes().await.unwrap().is_empty());
+
+        repository.finish_game(game, &outcome).await.unwrap();
+        assert_eq!(
+            repository.outcomes().await.unwrap(),
+            vec![(game, SavedOutcome::from(&outcome))]
+        );
+        assert!(matches!(
+            repository.append_turn(game + 1, &turns[2]).await,
+            Err(RepositoryError::NoSuchGame(_))
+        ));
+    }
+
+    pub async fn keeps_a_game_saved_partway_through(repository: &impl GameRepository) {
+        let (start, events, _) = played(5);
+        let turns = replay(&start, &events).unwrap();
+        // Saved as if it started there, so there is no earlier turn to tell who has melded
+        let underway = turns
+            .iter()
+            .position(|state| state.players.iter().any(|p| p.rack.played_initial_meld))
+            .expect("Someone melds in every game");
+        let game = repository.save_game(&turns[underway]).await.unwrap();
+        repository
+            .append_turn(game, &turns[underway + 1])
+            .await
+            .unwrap();
+        assert_same(&repository.load_game(game).await.unwrap(), &turns[underway]);
+        assert_same(
+            &repository.load_turn(game, 1).await.unwrap(),
+            &turns[underway + 1],
+        );
+    }
+
+    pub async fn refuses_events_from_another_game(repository: &impl GameRepository) {
+        let (_, events, outcome) = played(1);
+        let (other, _, _) = played(2);
+        assert!(matches!(
+            repository.save_played_game(&other, &events, &outcome).await,
+            Err(RepositoryError::NotReplayable)
+        ));
+        assert!(repository.list_games().await.unwrap().is_empty());
+    }
+}
+
+/// A throwaway database for each test, on the server given by TEST_DATABASE_URL.
+/// Tests which need one are ignored, run them with --ignored once it is set
+#[cfg(all(test, feature = "postgres"))]
+pub(crate) mod test_database {
+    use crate::persistence::postgres::PostgresRepository;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use sqlx::{ConnectOptions, Connection, Executor, PgPool};
+    use std::env;
+    use std::str::FromStr;
+
+    pub const TEST_DATABASE_URL: &str = "TEST_DATABASE_URL";
+
+    pub struct TestDatabase {
+        pub pool: PgPool,
+        server: PgConnectOptions,
+        name: String,
+    }
+
+    impl TestDatabase {
+        /// A new database, with every migration applied
+        pub async fn create() -> TestDatabase {
+            let db = TestDatabase::empty().await;
+            PostgresRepository::with_pool(db.pool.clone())
+                .migrate()
+                .await
+                .unwrap();
+            db
+        }
+
+        /// A new database with nothing in it, not even the rustikub schema
+        pub async fn empty() -> TestDatabase {
+            let url = env::var(TEST_DATABASE_URL)
+                .unwrap_or_else(|_| panic!("Set {} to run against Postgres", TEST_DATABASE_URL));
+            let server = PgConnectOptions::from_str(&url).expect("Not a Postgres URL");
+            let name = format!("rustikub_test_{:016x}", rand::random::<u64>());
+            let mut conn = server.connect().await.expect("Postgres is not running");
+            conn.execute(format!("create database {}", name).as_str())
+                .await
+                .unwrap();
+            conn.close().await.unwrap();
+
+            let options = server.clone().database(&name);
+            let pool = PgPoolOptions::new()
+                .connect_with(options.options([("search_path", "rustikub")]))
+                .await
+                .unwrap();
+            TestDatabase { pool, server, name }
+        }
+
+        pub async fn drop_database(self) {
+            self.pool.close().await;
+            let mut conn = self.server.connect().await.unwrap();
+            conn.execute(format!("drop database {} with (force)", self.name).as_str())
+                .await
+                .unwrap();
+        }
+    }
+}
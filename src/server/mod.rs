@@ -0,0 +1,672 @@
+//! Hosts games for remote players over TCP. Every message is a single line of JSON, see protocol.
+//! The server owns the only GameState, so clients never see each other's racks or the boneyard.
+//! Browsers can play the same games over a WebSocket instead, see web.
+
+pub mod protocol;
+pub mod web;
+
+use crate::domain::player::info::PlayerInfo;
+use crate::domain::player::rack::Rack;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::domain::tiles::tile_sequence::TileSequenceType;
+use crate::game_loop::events::{EventSink, GameEvent};
+use crate::game_loop::main_game_loop_with_events;
+use crate::game_loop::meta::{GameConfig, GameOutcome};
+use crate::game_loop::observation::{Observation, Seen};
+use crate::game_loop::state::GameState;
+use crate::game_loop::strategy::{all_strategies, MovePath, Strategy};
+use crate::game_loop::validation::validate_placement;
+use protocol::{ClientMessage, ServerMessage};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpListener;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+/// The seats at a hosted game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// How many remote players to wait for before starting
+    pub players: u8,
+    /// Bots seated after the remote players
+    pub bots: u8,
+    pub seed: Option<u64>,
+    /// Milliseconds each player has for a turn, see GameConfig::turn_limit
+    pub turn_millis: Option<u64>,
+}
+
+impl ServerConfig {
+    /// Why these seats can't make a game, if they can't
+    pub fn refusal(&self) -> Option<String> {
+        if self.players == 0 || self.players as usize + self.bots as usize > 4 {
+            Some("A game needs at least one player, and at most four seats".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            players: 2,
+            bots: 0,
+            seed: None,
+            turn_millis: None,
+        }
+    }
+}
+
+/// How long a new connection has to send its Join before it is hung up on
+pub const JOIN_WAIT: Duration = Duration::from_secs(10);
+
+type Connection = (Lines<BufReader<OwnedReadHalf>>, OwnedWriteHalf);
+
+/// Hosts a single game. Waits until every remote seat has joined, plays the whole game, and
+/// returns once every player has been told how it ended. Only the listener failing stops it
+/// early, anyone who goes quiet or sends nonsense while joining is just turned away
+pub async fn host_game(listener: TcpListener, conf: ServerConfig) -> io::Result<GameOutcome> {
+    if let Some(reason) = conf.refusal() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, reason));
+    }
+    // Every connection waits for its first line on a task of its own, so nobody holds up the rest
+    let (arrived, mut joins) = unbounded_channel();
+    let mut joined: Vec<(String, Connection)> = vec![];
+    while joined.len() < conf.players as usize {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let arrived = arrived.clone();
+                tokio::spawn(async move {
+                    let (read, write) = stream.into_split();
+                    let mut lines = BufReader::new(read).lines();
+                    let first = tokio::time::timeout(JOIN_WAIT, lines.next_line()).await;
+                    let _ = arrived.send((first, (lines, write)));
+                });
+            }
+            Some((first, (lines, mut write))) = joins.recv() => {
+                let refused = match first {
+                    Ok(Ok(Some(line))) => match serde_json::from_str(&line) {
+                        Ok(ClientMessage::Join { name }) => {
+                            match refuse_name(&name, joined.iter().map(|(taken, _)| taken)) {
+                                Some(reason) => reason,
+                                None => {
+                                    joined.push((name, (lines, write)));
+                                    continue;
+                                }
+                            }
+                        }
+                        _ => "Join the game first".to_string(),
+                    },
+                    // Gone before saying anything
+                    Ok(Ok(None)) => continue,
+                    Ok(Err(e)) => format!("Not a message: {}", e),
+                    Err(_) => "Took too long to join".to_string(),
+                };
+                // Not much to be done if they have already gone
+                let _ = send(&mut write, &ServerMessage::Refused { reason: refused }).await;
+            }
+        }
+    }
+
+    let names = joined.iter().map(|(name, ..)| name.clone()).collect();
+    let (seats, game) = start_game(names, &conf);
+    let mut writers = vec![];
+    for (seat, (_, (lines, write))) in seats.into_iter().zip(joined) {
+        writers.push(tokio::spawn(write_messages(write, seat.outbox)));
+        tokio::spawn(read_messages(lines, seat.inbox, seat.to_client));
+    }
+    let outcome = game.await.expect("The game loop does not panic");
+    for writer in writers {
+        let _ = writer.await;
+    }
+    Ok(outcome)
+}
+
+/// One remote player's end of a game
+pub(crate) struct Seat {
+    pub info: PlayerInfo,
+    /// Everything to be sent to the player, in order
+    pub outbox: UnboundedReceiver<ServerMessage>,
+    /// Where the player's messages go
+    pub inbox: UnboundedSender<ClientMessage>,
+    /// For answering the player straight away, like refusing a message
+    pub to_client: UnboundedSender<ServerMessage>,
+}
+
+/// Bots are named after their seat, with a prefix no remote player may use, see refuse_name
+fn bot_name(seat: usize) -> PlayerInfo {
+    PlayerInfo::of(&format!("bot {}", seat))
+}
+
+/// Why a remote player may not go by this name, if they may not. Seat numbers and bot names are
+/// kept apart from player names, since anyone sharing a name with a seat would see its tiles
+pub(crate) fn refuse_name<'a>(
+    name: &str,
+    mut taken: impl Iterator<Item = &'a String>,
+) -> Option<String> {
+    if name.trim().is_empty() {
+        Some("A name is needed to join".to_string())
+    } else if name.parse::<u32>().is_ok() || name.to_lowercase().starts_with("bot ") {
+        Some(format!("The name {} is kept for the seats", name))
+    } else if taken.any(|taken| taken == name) {
+        Some(format!("The name {} is already taken", name))
+    } else {
+        None
+    }
+}
+
+/// Seats the remote players in the order given, followed by the bots, and starts the game.
+/// Each player is welcomed before anything else is sent to them
+pub(crate) fn start_game(
+    names: Vec<String>,
+    conf: &ServerConfig,
+) -> (Vec<Seat>, JoinHandle<GameOutcome>) {
+    let turn_limit = conf.turn_millis.map(Duration::from_millis);
+    let mut strategies: Vec<Arc<dyn Strategy>> = vec![];
+    let mut seats = vec![];
+    for name in names {
+        let (to_client, outbox) = unbounded_channel();
+        let (inbox, from_client) = unbounded_channel();
+        strategies.push(Arc::new(Remote {
+            to_client: to_client.clone(),
+            from_client: Mutex::new(from_client),
+            limit: turn_limit,
+        }));
+        seats.push(Seat {
+            info: PlayerInfo::of(&name),
+            outbox,
+            inbox,
+            to_client,
+        });
+    }
+    strategies.extend(
+        all_strategies()
+            .into_iter()
+            .cycle()
+            .take(conf.bots as usize),
+    );
+
+    let mut state = GameState::init_game(GameConfig {
+        num_players: seats.len() as u8 + conf.bots,
+        seed: conf.seed,
+        strategies,
+        turn_limit,
+        ..GameConfig::default()
+    });
+    let mut sink = Broadcast {
+        seats: vec![],
+        face_up: state.table.face_up.clone(),
+        boneyard: state.table.boneyard.bones.len(),
+    };
+    for (player, seat) in state.players.iter_mut().zip(&seats) {
+        player.info = seat.info.clone();
+        let _ = seat.to_client.send(ServerMessage::Welcome {
+            you: seat.info.clone(),
+            seats: seats.len() as u8 + conf.bots,
+        });
+        sink.seats.push((seat.info.clone(), seat.to_client.clone()));
+    }
+    for (i, bot) in state.players.iter_mut().enumerate().skip(seats.len()) {
+        bot.info = bot_name(i + 1);
+    }
+
+    // The game loop blocks while waiting on each remote player, so it gets a thread of its own
+    let game = tokio::task::spawn_blocking(move || main_game_loop_with_events(state, &mut sink));
+    (seats, game)
+}
+
+async fn send(write: &mut OwnedWriteHalf, message: &ServerMessage) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).expect("Messages are always serializable");
+    line.push('\n');
+    write.write_all(line.as_bytes()).await
+}
+
+/// Writes until the game is over, or the client has gone
+async fn write_messages(mut write: OwnedWriteHalf, mut outbox: UnboundedReceiver<ServerMessage>) {
+    while let Some(message) = outbox.recv().await {
+        if send(&mut write, &message).await.is_err() {
+            return;
+        }
+        if let ServerMessage::Event {
+            event: GameEvent::GameEnded { .. },
+        } = message
+        {
+            return;
+        }
+    }
+}
+
+/// Passes on everything the client sends, answering anything unreadable straight away
+async fn read_messages(
+    mut lines: Lines<BufReader<OwnedReadHalf>>,
+    inbox: UnboundedSender<ClientMessage>,
+    to_client: UnboundedSender<ServerMessage>,
+) {
+    while let Ok(Some(line)) = lines.next_line().await {
+        match serde_json::from_str(&line) {
+            Ok(message) => {
+                if inbox.send(message).is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                let reason = format!("Not a message: {}", e);
+                let _ = to_client.send(ServerMessage::Refused { reason });
+            }
+        }
+    }
+}
+
+/// A player connected to the server. Each turn they are sent what they may see, and asked for a
+/// turn until they propose one that passes validation, decide to draw, or run out of time
+struct Remote {
+    to_client: UnboundedSender<ServerMessage>,
+    from_client: Mutex<UnboundedReceiver<ClientMessage>>,
+    limit: Option<Duration>,
+}
+
+impl Strategy for Remote {
+    fn name(&self) -> &str {
+        "remote"
+    }
+
+    /// Only ever plays whole turns, see propose_turn
+    fn rearrange(&self, rack: &Rack, face_up: &FaceUpTiles) -> Option<(Rack, FaceUpTiles)> {
+        None
+    }
+
+    fn propose_turn(
+        &self,
+        observation: &Observation,
+    ) -> Option<(Rack, FaceUpTiles, Vec<MovePath>)> {
+        let mut from_client = self
+            .from_client
+            .lock()
+            .expect("Only used from the game loop");
+        // Anything sent too late for an earlier turn is not meant for this one
+        while from_client.try_recv().is_ok() {}
+        let _ = self.to_client.send(ServerMessage::YourTurn {
+            observation: observation.clone(),
+        });
+        let rack = &observation.rack;
+        let deadline = self.limit.map(|limit| tokio::time::Instant::now() + limit);
+        // Once a player has disconnected or run out of time, they draw, or take the penalty
+        while let Some(message) = receive(&mut from_client, deadline) {
+            let face_up = match message {
+                ClientMessage::Draw => return None,
+                ClientMessage::Join { .. } => {
+                    let reason = "Already joined".to_string();
+                    let _ = self.to_client.send(ServerMessage::Refused { reason });
+                    continue;
+                }
+                ClientMessage::ProposeTurn { face_up } => face_up,
+            };
+            let placed = TileSequenceType::of(&face_up).difference(&observation.face_up);
+            let proposed_rack = Rack {
+                rack: TileSequenceType::of(rack).difference(&placed),
+                played_initial_meld: rack.played_initial_meld,
+            };
+            match validate_placement(rack, &observation.face_up, &proposed_rack, &face_up) {
+                Ok(()) => return Some((proposed_rack, face_up, vec![MovePath::Remote])),
+                Err(violation) => {
+                    let reason = format!("{:#}", violation);
+                    let _ = self.to_client.send(ServerMessage::TurnRejected { reason });
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Waits for the next message, but no longer than the deadline
+fn receive(
+    from_client: &mut UnboundedReceiver<ClientMessage>,
+    deadline: Option<tokio::time::Instant>,
+) -> Option<ClientMessage> {
+    match deadline {
+        None => from_client.blocking_recv(),
+        Some(deadline) => Handle::current().block_on(async {
+            tokio::time::timeout_at(deadline, from_client.recv())
+                .await
+                .ok()
+                .flatten()
+        }),
+    }
+}
+
+/// Tells every player what happened, except for the tiles dealt to, drawn or taken as a penalty by anyone else.
+/// After every turn, everyone is also sent the table as it is now
+struct Broadcast {
+    seats: Vec<(PlayerInfo, UnboundedSender<ServerMessage>)>,
+    face_up: FaceUpTiles,
+    boneyard: usize,
+}
+
+impl Broadcast {
+    fn send_all(&self, message: impl Fn(&PlayerInfo) -> ServerMessage) {
+        for (seat, to_client) in &self.seats {
+            let _ = to_client.send(message(seat));
+        }
+    }
+}
+
+impl EventSink for Broadcast {
+    fn record(&mut self, event: &GameEvent) {
+        self.send_all(|seat| Seen::by(seat, event).into());
+        if !event.ends_turn() {
+            return;
+        }
+        match event {
+            GameEvent::TileDrawn { .. } => self.boneyard -= 1,
+            GameEvent::TimedOut { penalty, .. } => self.boneyard -= penalty.len(),
+            GameEvent::InitialMeldPlayed { sets, .. } | GameEvent::SetsPlaced { sets, .. } => {
+                self.face_up.sets.extend(sets.iter().cloned())
+            }
+            GameEvent::TableRearranged { diff, .. } => {
+                self.face_up = diff.apply(&self.face_up).expect("Diff of this table")
+            }
+            _ => {}
+        }
+        self.send_all(|_| ServerMessage::TableUpdated {
+            face_up: self.face_up.clone(),
+            boneyard: self.boneyard,
+        });
+    }
+}
+
+#[cfg(test)]
+mod server_tests {
+    use super::*;
+    use crate::game_loop::clock::PENALTY_TILES;
+    use crate::game_loop::strategy::default_strategy;
+    use tokio::net::TcpStream;
+
+    async fn start(conf: ServerConfig) -> (String, JoinHandle<io::Result<GameOutcome>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        (address, tokio::spawn(host_game(listener, conf)))
+    }
+
+    struct Client {
+        lines: Lines<BufReader<OwnedReadHalf>>,
+        write: OwnedWriteHalf,
+    }
+
+    impl Client {
+        async fn join(address: &str, name: &str) -> Client {
+            let (read, write) = TcpStream::connect(address).await.unwrap().into_split();
+            let mut client = Client {
+                lines: BufReader::new(read).lines(),
+                write,
+            };
+            client
+                .send_line(&format!(r#"{{"type":"Join","name":"{}"}}"#, name))
+                .await;
+            client
+        }
+
+        async fn send(&mut self, message: &ClientMessage) {
+            self.send_line(&serde_json::to_string(message).unwrap())
+                .await;
+        }
+
+        async fn send_line(&mut self, line: &str) {
+            self.write
+                .write_all(format!("{}\n", line).as_bytes())
+                .await
+                .unwrap();
+        }
+
+        async fn receive(&mut self) -> Option<ServerMessage> {
+            let line = self.lines.next_line().await.unwrap()?;
+            Some(serde_json::from_str(&line).unwrap())
+        }
+
+        /// Plays with the default strategy, knowing only what the server sends
+        async fn play_out(mut self) -> Vec<ServerMessage> {
+            let mut seen = vec![];
+            while let Some(message) = self.receive().await {
+                match &message {
+                    ServerMessage::YourTurn { observation } => {
+                        match default_strategy().propose_turn(observation) {
+                            Some((_, face_up, _)) => {
+                                self.send(&ClientMessage::ProposeTurn { face_up }).await
+                            }
+                            None => self.send(&ClientMessage::Draw).await,
+                        }
+                    }
+                    ServerMessage::TurnRejected { .. } => self.send(&ClientMessage::Draw).await,
+                    _ => {}
+                }
+                seen.push(message);
+            }
+            seen
+        }
+    }
+
+    #[tokio::test]
+    async fn plays_a_whole_game_with_loopback_clients() {
+        let (address, server) = start(ServerConfig {
+            players: 2,
+            bots: 1,
+            seed: Some(12),
+            ..ServerConfig::default()
+        })
+        .await;
+        let alice = tokio::spawn(Client::join(&address, "alice").await.play_out());
+        let bob = tokio::spawn(Client::join(&address, "bob").await.play_out());
+        let outcome = server.await.unwrap().unwrap();
+        let alice = alice.await.unwrap();
+        let bob = bob.await.unwrap();
+
+        for (me, seen) in [("alice", &alice), ("bob", &bob)] {
+            let me = PlayerInfo::of(&me.to_string());
+            assert_eq!(
+                seen[0],
+                ServerMessage::Welcome {
+                    you: me.clone(),
+                    seats: 3
+                }
+            );
+            assert!(seen
+                .iter()
+                .any(|m| matches!(m, ServerMessage::YourTurn { .. })));
+            // Only ever their own tiles
+            for message in seen {
+                match message {
+                    ServerMessage::Event {
+                        event: GameEvent::InitialDeal { player, .. },
+                    }
+                    | ServerMessage::Event {
+                        event: GameEvent::TileDrawn { player, .. },
+                    } => assert_eq!(player, &me),
+                    ServerMessage::OtherDrew { player } => assert_ne!(player, &me),
+                    // Nor the seed, which would give away every rack and the boneyard
+                    ServerMessage::Event {
+                        event: GameEvent::GameStarted { .. },
+                    } => panic!("{} was sent the seed", me),
+                    _ => {}
+                }
+                assert!(!serde_json::to_string(message).unwrap().contains("seed"));
+            }
+            assert!(seen
+                .iter()
+                .any(|m| matches!(m, ServerMessage::GameStarted { num_players: 3, .. })));
+            assert_eq!(
+                seen.last(),
+                Some(&ServerMessage::Event {
+                    event: GameEvent::GameEnded {
+                        end: outcome.end,
+                        winner: outcome.winner.info.clone(),
+                        turns: outcome.turns
+                    }
+                })
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn never_shares_a_name_with_a_bot() {
+        let (address, server) = start(ServerConfig {
+            players: 1,
+            bots: 1,
+            seed: Some(6),
+            ..ServerConfig::default()
+        })
+        .await;
+        // The bot sits in seat 2, so going by 2 would be sent its tiles
+        for name in ["2", "bot 2", " "] {
+            let mut impostor = Client::join(&address, name).await;
+            assert!(matches!(
+                impostor.receive().await,
+                Some(ServerMessage::Refused { .. })
+            ));
+        }
+        let seen = Client::join(&address, "2b").await.play_out().await;
+        server.await.unwrap().unwrap();
+
+        let me = PlayerInfo::of(&"2b".to_string());
+        let bot = PlayerInfo::of(&"bot 2".to_string());
+        assert!(seen.contains(&ServerMessage::OtherDealt {
+            player: bot,
+            tiles: 14
+        }));
+        for message in &seen {
+            if let ServerMessage::Event {
+                event: GameEvent::InitialDeal { player, .. } | GameEvent::TileDrawn { player, .. },
+            } = message
+            {
+                assert_eq!(player, &me);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn nobody_holds_up_the_lobby() {
+        let (address, server) = start(ServerConfig {
+            players: 1,
+            bots: 1,
+            seed: Some(9),
+            ..ServerConfig::default()
+        })
+        .await;
+        // Connects but never joins, while everyone else gets on with it
+        let _silent = TcpStream::connect(&address).await.unwrap();
+        let (_, mut garbled) = TcpStream::connect(&address).await.unwrap().into_split();
+        garbled.write_all(&[0xff, 0xfe, b'\n']).await.unwrap();
+        let (_, mut hung_up) = TcpStream::connect(&address).await.unwrap().into_split();
+        hung_up.shutdown().await.unwrap();
+
+        let seen = Client::join(&address, "frank").await.play_out().await;
+        assert!(matches!(
+            seen.last(),
+            Some(ServerMessage::Event {
+                event: GameEvent::GameEnded { .. }
+            })
+        ));
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn needs_between_one_and_four_seats() {
+        for (players, bots) in [(0, 2), (3, 2)] {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let conf = ServerConfig {
+                players,
+                bots,
+                ..ServerConfig::default()
+            };
+            let refused = host_game(listener, conf).await.unwrap_err();
+            assert_eq!(refused.kind(), io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[tokio::test]
+    async fn explains_what_is_not_allowed() {
+        let (address, server) = start(ServerConfig {
+            players: 1,
+            bots: 1,
+            seed: Some(3),
+            ..ServerConfig::default()
+        })
+        .await;
+        // Has to join before anything else
+        let (read, mut write) = TcpStream::connect(&address).await.unwrap().into_split();
+        write.write_all(b"{\"type\":\"Draw\"}\n").await.unwrap();
+        let mut stranger = Client {
+            lines: BufReader::new(read).lines(),
+            write,
+        };
+        assert_eq!(
+            stranger.receive().await,
+            Some(ServerMessage::Refused {
+                reason: "Join the game first".to_string()
+            })
+        );
+
+        let mut carol = Client::join(&address, "carol").await;
+        assert!(matches!(
+            carol.receive().await,
+            Some(ServerMessage::Welcome { .. })
+        ));
+        carol.send_line("hello").await;
+        let mut refused = false;
+        let mut rejected = false;
+        while let Some(message) = carol.receive().await {
+            match message {
+                ServerMessage::Refused { reason } => refused = reason.contains("Not a message"),
+                ServerMessage::YourTurn { observation } if !rejected => {
+                    // Proposing the table as it already is places nothing
+                    let face_up = observation.face_up;
+                    carol.send(&ClientMessage::ProposeTurn { face_up }).await
+                }
+                ServerMessage::TurnRejected { reason } => {
+                    assert_eq!(reason, "No tiles were placed from the rack");
+                    rejected = true;
+                    // Walks away, and is left drawing for the rest of the game
+                    break;
+                }
+                _ => {}
+            }
+        }
+        drop(carol);
+        server.await.unwrap().unwrap();
+        assert!(refused);
+        assert!(rejected);
+    }
+
+    #[tokio::test]
+    async fn too_slow_takes_the_penalty() {
+        let (address, server) = start(ServerConfig {
+            players: 1,
+            bots: 1,
+            seed: Some(4),
+            turn_millis: Some(100),
+        })
+        .await;
+        let mut dave = Client::join(&address, "dave").await;
+        let mut penalized = false;
+        // Never answers, so the first turn runs out
+        while let Some(message) = dave.receive().await {
+            if let ServerMessage::Event {
+                event: GameEvent::TimedOut { player, penalty },
+            } = message
+            {
+                assert_eq!(player, PlayerInfo::of(&"dave".to_string()));
+                assert_eq!(penalty.len(), PENALTY_TILES);
+                penalized = true;
+                break;
+            }
+        }
+        drop(dave);
+        server.await.unwrap().unwrap();
+        assert!(penalized);
+    }
+}
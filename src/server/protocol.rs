@@ -0,0 +1,70 @@
+use crate::domain::player::info::PlayerInfo;
+use crate::domain::player::rack::Rack;
+use crate::domain::table::face_up::FaceUpTiles;
+use crate::game_loop::events::GameEvent;
+use crate::game_loop::observation::{Observation, Seen};
+use serde::{Deserialize, Serialize};
+
+/// Sent by a client to the server, as one JSON object per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    /// Must be the first message, takes a seat at the game under this name
+    Join { name: String },
+    /// The whole table as it should be at the end of the turn. The tiles placed are worked out
+    /// by the server, from what is new on the table
+    ProposeTurn { face_up: FaceUpTiles },
+    /// Ends the turn without placing anything
+    Draw,
+}
+
+/// Sent by the server to a client, as one JSON object per line.
+/// A client only ever sees its own rack, and never the boneyard, only how many tiles are left in it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    /// The seat was taken, the game starts once every seat is
+    Welcome { you: PlayerInfo, seats: u8 },
+    /// The last message could not be understood, or was not allowed
+    Refused { reason: String },
+    /// Everything the player is allowed to know, it is now their turn
+    YourTurn { observation: Observation },
+    /// The proposed turn broke the rules, it is still the player's turn
+    TurnRejected { reason: String },
+    /// The game has started, without the seed it was dealt from
+    GameStarted {
+        num_players: u8,
+        strategies: Vec<String>,
+    },
+    /// Something that happened in the game, which everyone may see
+    Event { event: GameEvent },
+    /// The table everyone can see, sent after every turn
+    TableUpdated {
+        face_up: FaceUpTiles,
+        boneyard: usize,
+    },
+    /// Someone else was dealt their tiles, which stay hidden
+    OtherDealt { player: PlayerInfo, tiles: usize },
+    /// Someone else drew a tile, which stays hidden
+    OtherDrew { player: PlayerInfo },
+    /// Someone else ran out of time, and drew this many penalty tiles
+    OtherTimedOut { player: PlayerInfo, tiles: usize },
+}
+
+impl From<Seen> for ServerMessage {
+    fn from(seen: Seen) -> Self {
+        match seen {
+            Seen::Event(event) => ServerMessage::Event { event },
+            Seen::Started {
+                num_players,
+                strategies,
+            } => ServerMessage::GameStarted {
+                num_players,
+                strategies,
+            },
+            Seen::OtherDealt { player, tiles } => ServerMessage::OtherDealt { player, tiles },
+            Seen::OtherDrew { player } => ServerMessage::OtherDrew { player },
+            Seen::OtherTimedOut { player, tiles } => ServerMessage::OtherTimedOut { player, tiles },
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! An HTTP API over the same games as the TCP server, for browsers.
+//!
+//! - `POST /lobbies` with a ServerConfig opens a lobby, `GET /lobbies` and `GET /lobbies/{id}` show them
+//! - `POST /lobbies/{id}/join` with `{"name": ...}` takes a seat, and answers with a secret token
+//! - `GET /lobbies/{id}/play?token=...` upgrades to a WebSocket once the game has started
+//!
+//! The game starts as soon as the last seat is taken. Over the WebSocket every text frame is
+//! one of the messages in protocol, so each player only ever sees their own rack. Anyone who has
+//! not connected by CONNECT_WAIT after the game started loses their seat, and draws every turn.
+
+use crate::domain::player::info::PlayerInfo;
+use crate::game_loop::events::GameEvent;
+use crate::server::protocol::ServerMessage;
+use crate::server::{refuse_name, start_game, Seat, ServerConfig};
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::Response;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+/// How long each player has to open their WebSocket once the game has started
+pub const CONNECT_WAIT: Duration = Duration::from_secs(60);
+
+/// Serves the API until the listener fails
+pub async fn serve_web(listener: TcpListener) -> io::Result<()> {
+    axum::serve(listener, router()).await
+}
+
+/// Every route, sharing one set of lobbies between every clone
+pub fn router() -> Router {
+    router_waiting(CONNECT_WAIT)
+}
+
+/// The same routes, but players only have connect_wait to connect before losing their seat
+pub fn router_waiting(connect_wait: Duration) -> Router {
+    let lobbies = Lobbies(Arc::new(Mutex::new(LobbyList {
+        next_id: 0,
+        lobbies: BTreeMap::new(),
+        connect_wait,
+    })));
+    Router::new()
+        .route("/lobbies", get(list_lobbies).post(open_lobby))
+        .route("/lobbies/{id}", get(show_lobby))
+        .route("/lobbies/{id}/join", post(join_lobby))
+        .route("/lobbies/{id}/play", get(play))
+        .with_state(lobbies)
+}
+
+/// What anyone can see of a lobby
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LobbyView {
+    pub id: u32,
+    pub conf: ServerConfig,
+    /// Names of the players who have taken a seat, in seating order
+    pub joined: Vec<String>,
+    pub status: LobbyStatus,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum LobbyStatus {
+    Waiting,
+    Playing,
+    Finished { winner: PlayerInfo, turns: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinRequest {
+    pub name: String,
+}
+
+/// Only ever sent to the player who joined, the token is needed to play
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Joined {
+    pub lobby: u32,
+    pub you: PlayerInfo,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct PlayQuery {
+    token: String,
+}
+
+type ApiError = (StatusCode, String);
+
+#[derive(Clone)]
+struct Lobbies(Arc<Mutex<LobbyList>>);
+
+struct LobbyList {
+    next_id: u32,
+    lobbies: BTreeMap<u32, Lobby>,
+    connect_wait: Duration,
+}
+
+struct Lobby {
+    conf: ServerConfig,
+    /// Name and token of every player who joined, in seating order
+    joined: Vec<(String, String)>,
+    /// Once the game has started, waiting to be picked up by each player's WebSocket.
+    /// Any left after the connect wait are dropped, see join_lobby
+    seats: HashMap<String, Seat>,
+    status: LobbyStatus,
+}
+
+impl Lobby {
+    fn view(&self, id: u32) -> LobbyView {
+        LobbyView {
+            id,
+            conf: self.conf.clone(),
+            joined: self.joined.iter().map(|(name, _)| name.clone()).collect(),
+            status: self.status.clone(),
+        }
+    }
+}
+
+impl LobbyList {
+    fn find(&mut self, id: u32) -> Result<&mut Lobby, ApiError> {
+        self.lobbies
+            .get_mut(&id)
+            .ok_or((StatusCode::NOT_FOUND, format!("There is no lobby {}", id)))
+    }
+}
+
+async fn list_lobbies(State(lobbies): State<Lobbies>) -> Json<Vec<LobbyView>> {
+    let list = lobbies.0.lock().expect("Lobbies are never poisoned");
+    Json(
+        list.lobbies
+            .iter()
+            .map(|(id, lobby)| lobby.view(*id))
+            .collect(),
+    )
+}
+
+async fn open_lobby(
+    State(lobbies): State<Lobbies>,
+    Json(conf): Json<ServerConfig>,
+) -> Result<(StatusCode, Json<LobbyView>), ApiError> {
+    if let Some(reason) = conf.refusal() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, reason));
+    }
+    let mut list = lobbies.0.lock().expect("Lobbies are never poisoned");
+    list.next_id += 1;
+    let id = list.next_id;
+    let lobby = Lobby {
+        conf,
+        joined: vec![],
+        seats: HashMap::new(),
+        status: LobbyStatus::Waiting,
+    };
+    let view = lobby.view(id);
+    list.lobbies.insert(id, lobby);
+    Ok((StatusCode::CREATED, Json(view)))
+}
+
+async fn show_lobby(
+    State(lobbies): State<Lobbies>,
+    Path(id): Path<u32>,
+) -> Result<Json<LobbyView>, ApiError> {
+    let mut list = lobbies.0.lock().expect("Lobbies are never poisoned");
+    Ok(Json(list.find(id)?.view(id)))
+}
+
+/// Takes a seat, and starts the game if it was the last one
+async fn join_lobby(
+    State(lobbies): State<Lobbies>,
+    Path(id): Path<u32>,
+    Json(request): Json<JoinRequest>,
+) -> Result<Json<Joined>, ApiError> {
+    let mut list = lobbies.0.lock().expect("Lobbies are never poisoned");
+    let connect_wait = list.connect_wait;
+    let lobby = list.find(id)?;
+    if lobby.status != LobbyStatus::Waiting {
+        return Err((
+            StatusCode::CONFLICT,
+            "The game has already started".to_string(),
+        ));
+    }
+    if let Some(reason) = refuse_name(&request.name, lobby.joined.iter().map(|(name, _)| name)) {
+        return Err((StatusCode::CONFLICT, reason));
+    }
+    let token = format!("{:016x}", rand::random::<u64>());
+    lobby.joined.push((request.name.clone(), token.clone()));
+
+    if lobby.joined.len() == lobby.conf.players as usize {
+        let names = lobby.joined.iter().map(|(name, _)| name.clone()).collect();
+        let (seats, game) = start_game(names, &lobby.conf);
+        lobby.seats = lobby
+            .joined
+            .iter()
+            .map(|(_, token)| token.clone())
+            .zip(seats)
+            .collect();
+        lobby.status = LobbyStatus::Playing;
+        let unclaimed = lobbies.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(connect_wait).await;
+            let mut list = unclaimed.0.lock().expect("Lobbies are never poisoned");
+            if let Ok(lobby) = list.find(id) {
+                // Without a seat nobody can answer for the player, so they draw every turn
+                lobby.seats.clear();
+            }
+        });
+        let lobbies = lobbies.clone();
+        tokio::spawn(async move {
+            let outcome = game.await.expect("The game loop does not panic");
+            let mut list = lobbies.0.lock().expect("Lobbies are never poisoned");
+            if let Ok(lobby) = list.find(id) {
+                lobby.status = LobbyStatus::Finished {
+                    winner: outcome.winner.info,
+                    turns: outcome.turns,
+                };
+            }
+        });
+    }
+    Ok(Json(Joined {
+        lobby: id,
+        you: PlayerInfo::of(&request.name),
+        token,
+    }))
+}
+
+/// Each seat can only be played from one WebSocket
+async fn play(
+    State(lobbies): State<Lobbies>,
+    Path(id): Path<u32>,
+    Query(query): Query<PlayQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let seat = {
+        let mut list = lobbies.0.lock().expect("Lobbies are never poisoned");
+        let lobby = list.find(id)?;
+        if !lobby.joined.iter().any(|(_, token)| *token == query.token) {
+            return Err((StatusCode::FORBIDDEN, "Join the game first".to_string()));
+        }
+        match lobby.status {
+            LobbyStatus::Waiting => {
+                let reason = "Still waiting for everyone to join".to_string();
+                return Err((StatusCode::CONFLICT, reason));
+            }
+            _ => lobby.seats.remove(&query.token).ok_or((
+                StatusCode::CONFLICT,
+                "Someone is already playing this seat, or it was not claimed in time".to_string(),
+            ))?,
+        }
+    };
+    Ok(upgrade.on_upgrade(move |socket| connect(socket, seat)))
+}
+
+/// Passes messages both ways until the game is over, or the player has gone.
+/// Once they have gone, they draw every turn for the rest of the game
+async fn connect(mut socket: WebSocket, seat: Seat) {
+    let Seat {
+        mut outbox,
+        inbox,
+        to_client,
+        ..
+    } = seat;
+    loop {
+        tokio::select! {
+            message = outbox.recv() => {
+                let Some(message) = message else { break };
+                let text = serde_json::to_string(&message).expect("Messages are always serializable");
+                if socket.send(Message::Text(text.into())).await.is_err() {
+                    return;
+                }
+                if let ServerMessage::Event { event: GameEvent::GameEnded { .. } } = message {
+                    break;
+                }
+            }
+            received = socket.recv() => match received {
+                Some(Ok(Message::Text(text))) => match serde_json::from_str(&text) {
+                    Ok(message) => {
+                        let _ = inbox.send(message);
+                    }
+                    Err(e) => {
+                        let reason = format!("Not a message: {}", e);
+                        let _ = to_client.send(ServerMessage::Refused { reason });
+                    }
+                },
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
@@ -1,15 +1,37 @@
 #[cfg(test)]
 mod end_to_end_run_game_for_debugging {
-
-    // Doesn't work because not set up as library?
-    // https://www.reddit.com/r/rust/comments/ksenfh/unable_to_import_crate_into_integration_tests/
-    // use crate::rustikub::
+    use rustikub::domain::Decompose;
+    use rustikub::{main_game_loop, GameConfig, GameState};
 
     #[test]
     fn run_game() {
-        // let conf = GameConfig { num_players: 4 };
-        // let game1 = GameState::init_game(conf);
-        // let result = main_game_loop(game1);
+        let conf = GameConfig {
+            num_players: 4,
+            ..GameConfig::default()
+        };
+        let game1 = GameState::init_game(conf);
+        println!("Game Seed: {}", game1.seed);
+        let result = main_game_loop(game1);
+        let huh = format!("Test {}", result);
+        println!("{huh}");
+    }
+
+    #[test]
+    fn game_starts_with_every_tile() {
+        let conf = GameConfig {
+            num_players: 3,
+            seed: Some(7),
+            ..GameConfig::default()
+        };
+        let game = GameState::init_game(conf);
+        let on_racks: usize = game
+            .players
+            .iter()
+            .map(|p| p.rack.count().unwrap().0 as usize)
+            .sum();
+        assert_eq!(game.players.len(), 3);
+        assert_eq!(on_racks + game.table.boneyard.bones.len(), 106);
+        assert!(game.table.face_up.sets.is_empty());
     }
 
     #[test]
@@ -0,0 +1,204 @@
+#[cfg(test)]
+mod web_api_tests {
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use axum::Router;
+    use futures_util::{SinkExt, StreamExt};
+    use rustikub::domain::player::info::PlayerInfo;
+    use rustikub::game_loop::events::GameEvent;
+    use rustikub::game_loop::strategy::default_strategy;
+    use rustikub::server::protocol::{ClientMessage, ServerMessage};
+    use rustikub::server::web::{router, router_waiting, Joined, LobbyStatus, LobbyView};
+    use serde_json::json;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::connect_async;
+    use tokio_tungstenite::tungstenite::Message;
+    use tower::ServiceExt;
+
+    /// Calls the API in process, without going over the network
+    async fn call(
+        app: &Router,
+        method: &str,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    async fn lobby(app: &Router, id: u32) -> LobbyView {
+        let (status, body) = call(app, "GET", &format!("/lobbies/{}", id), json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        serde_json::from_str(&body).unwrap()
+    }
+
+    async fn join(app: &Router, id: u32, name: &str) -> (StatusCode, String) {
+        let uri = format!("/lobbies/{}/join", id);
+        call(app, "POST", &uri, json!({ "name": name })).await
+    }
+
+    /// WebSockets need a real connection, so the same lobbies are also served on a local port
+    async fn serve(app: &Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let app = app.clone();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        address
+    }
+
+    /// Plays with the default strategy, knowing only what the server sends
+    async fn play_out(address: String, joined: Joined) -> Vec<ServerMessage> {
+        let url = format!(
+            "ws://{}/lobbies/{}/play?token={}",
+            address, joined.lobby, joined.token
+        );
+        let (mut socket, _) = connect_async(url).await.unwrap();
+        let mut seen = vec![];
+        while let Some(Ok(frame)) = socket.next().await {
+            let Message::Text(text) = frame else { continue };
+            let message: ServerMessage = serde_json::from_str(&text).unwrap();
+            let reply = match &message {
+                ServerMessage::YourTurn { observation } => {
+                    match default_strategy().propose_turn(observation) {
+                        Some((_, face_up, _)) => Some(ClientMessage::ProposeTurn { face_up }),
+                        None => Some(ClientMessage::Draw),
+                    }
+                }
+                ServerMessage::TurnRejected { .. } => Some(ClientMessage::Draw),
+                _ => None,
+            };
+            if let Some(reply) = reply {
+                let text = serde_json::to_string(&reply).unwrap();
+                socket.send(Message::Text(text.into())).await.unwrap();
+            }
+            seen.push(message);
+        }
+        seen
+    }
+
+    #[tokio::test]
+    async fn lobby_to_finished_game() {
+        let app = router();
+        let address = serve(&app).await;
+        let (status, body) = call(
+            &app,
+            "POST",
+            "/lobbies",
+            json!({ "players": 2, "bots": 1, "seed": 8 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CREATED);
+        let id = serde_json::from_str::<LobbyView>(&body).unwrap().id;
+
+        let (status, body) = join(&app, id, "alice").await;
+        assert_eq!(status, StatusCode::OK);
+        let alice: Joined = serde_json::from_str(&body).unwrap();
+        assert_eq!(join(&app, id, "alice").await.0, StatusCode::CONFLICT);
+        // The bot sits in seat 3, and only the bot may see its tiles
+        assert_eq!(join(&app, id, "3").await.0, StatusCode::CONFLICT);
+        assert_eq!(lobby(&app, id).await.status, LobbyStatus::Waiting);
+        let bob: Joined = serde_json::from_str(&join(&app, id, "bob").await.1).unwrap();
+        assert_eq!(join(&app, id, "carol").await.0, StatusCode::CONFLICT);
+        let view = lobby(&app, id).await;
+        assert_eq!(view.joined, vec!["alice", "bob"]);
+        assert_eq!(view.status, LobbyStatus::Playing);
+
+        let stolen = format!("ws://{}/lobbies/{}/play?token=nope", address, id);
+        assert!(connect_async(stolen).await.is_err());
+        let alice = tokio::spawn(play_out(address.clone(), alice));
+        let bob = tokio::spawn(play_out(address.clone(), bob));
+        for (me, seen) in [("alice", alice.await.unwrap()), ("bob", bob.await.unwrap())] {
+            let me = PlayerInfo::of(&me.to_string());
+            assert_eq!(
+                seen[0],
+                ServerMessage::Welcome {
+                    you: me.clone(),
+                    seats: 3
+                }
+            );
+            assert!(seen
+                .iter()
+                .any(|m| matches!(m, ServerMessage::TableUpdated { .. })));
+            for message in &seen {
+                if let ServerMessage::Event {
+                    event:
+                        GameEvent::InitialDeal { player, .. } | GameEvent::TileDrawn { player, .. },
+                } = message
+                {
+                    assert_eq!(player, &me);
+                }
+            }
+            assert!(matches!(
+                seen.last(),
+                Some(ServerMessage::Event {
+                    event: GameEvent::GameEnded { .. }
+                })
+            ));
+        }
+
+        // The lobby is told once the game loop itself has finished
+        for _ in 0..50 {
+            if matches!(lobby(&app, id).await.status, LobbyStatus::Finished { .. }) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("The lobby never finished");
+    }
+
+    #[tokio::test]
+    async fn plays_on_without_someone_who_never_connects() {
+        let app = router_waiting(Duration::from_millis(200));
+        let address = serve(&app).await;
+        let (_, body) = call(&app, "POST", "/lobbies", json!({ "players": 2, "seed": 3 })).await;
+        let id = serde_json::from_str::<LobbyView>(&body).unwrap().id;
+        let alice: Joined = serde_json::from_str(&join(&app, id, "alice").await.1).unwrap();
+        let bob: Joined = serde_json::from_str(&join(&app, id, "bob").await.1).unwrap();
+
+        // Bob never connects, so once their seat is dropped they draw every turn
+        let seen = play_out(address.clone(), alice).await;
+        assert!(matches!(
+            seen.last(),
+            Some(ServerMessage::Event {
+                event: GameEvent::GameEnded { .. }
+            })
+        ));
+        let late = format!(
+            "ws://{}/lobbies/{}/play?token={}",
+            address, bob.lobby, bob.token
+        );
+        assert!(connect_async(late).await.is_err());
+        for _ in 0..50 {
+            if matches!(lobby(&app, id).await.status, LobbyStatus::Finished { .. }) {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("The lobby never finished");
+    }
+
+    #[tokio::test]
+    async fn refuses_what_does_not_exist() {
+        let app = router();
+        assert_eq!(join(&app, 7, "alice").await.0, StatusCode::NOT_FOUND);
+        let (status, _) = call(&app, "POST", "/lobbies", json!({ "players": 4, "bots": 1 })).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        let (status, body) = call(&app, "GET", "/lobbies", json!(null)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            serde_json::from_str::<Vec<LobbyView>>(&body).unwrap(),
+            vec![]
+        );
+    }
+}